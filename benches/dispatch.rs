@@ -0,0 +1,75 @@
+//! Measures the cost of delivering a message while many other actors are bound
+//! but idle. With dispatch driven by the ready queue the rate should not depend
+//! on the number of idle actors.
+//!
+//! Run with `cargo bench --bench dispatch`.
+
+use doing_more_actors::{Actor, Context, Message, System};
+use std::time::Instant;
+
+#[derive(Debug, Clone)]
+enum Msg {
+    Ping(u32),
+    Wake,
+}
+
+impl Message for Msg {}
+
+/// Bounces a counter to its peer until it runs out.
+#[derive(Debug)]
+struct Player {
+    peer: String,
+}
+
+impl Actor for Player {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Ping(0) => {
+                ctx.stop(tag);
+                ctx.stop(&self.peer);
+            }
+            Msg::Ping(n) => ctx.send(&self.peer, Msg::Ping(n - 1)),
+            Msg::Wake => (),
+        }
+    }
+}
+
+/// Returns the number of messages delivered per second.
+fn ping_pong(idle: usize, messages: u32) -> f64 {
+    let mut sys = System::default();
+    for n in 0..idle {
+        let tag = format!("idle-{n}");
+        // Every idle actor has had a mailbox once.
+        sys.bind(tag.clone(), Player { peer: tag }).send(Msg::Wake);
+    }
+    sys.step(0);
+    let ping = sys.bind(
+        "ping".to_string(),
+        Player {
+            peer: "pong".to_string(),
+        },
+    );
+    sys.bind(
+        "pong".to_string(),
+        Player {
+            peer: "ping".to_string(),
+        },
+    );
+    ping.send(Msg::Ping(messages));
+
+    let started = Instant::now();
+    sys.run_virtual(0);
+    assert!(!ping.is_alive());
+    messages as f64 / started.elapsed().as_secs_f64()
+}
+
+fn main() {
+    let messages = 100_000;
+    for idle in [0, 1_000, 100_000] {
+        let rate = ping_pong(idle, messages);
+        println!("{idle:>7} idle actors: {rate:>12.0} msg/s");
+    }
+}
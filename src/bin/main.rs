@@ -1,4 +1,4 @@
-use doing_more_actors::{Actor, Context, Message, Millis, System};
+use doing_more_actors::{FsmActor, FsmState, Message, Millis, Schedule, System, Transition};
 
 #[derive(Debug, Clone)]
 enum Protocol {
@@ -7,46 +7,44 @@ enum Protocol {
 
 impl Message for Protocol {}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum State {
     Empty,
-    Counter(usize, Millis),
+    Counter,
     Done,
 }
 
-impl Actor for State {
-    type T = String;
-    type M = Protocol;
+impl FsmState for State {}
 
-    fn act(&mut self, tag: &Self::T, ctx: &mut Context<Self::T, Self, Self::M>, msg: Self::M) {
-        println!("[tag={:?}] state={:?} message={:?}", tag, self, msg);
-        match self {
-            State::Empty => {
-                ctx.send(tag, msg);
-                *self = State::Counter(0, ctx.now());
-            }
-            State::Counter(10, time) => {
-                println!("time: {}", ctx.now() - *time);
-                *time = ctx.now();
-                ctx.send(tag, msg);
-                *self = State::Done;
-            }
-            State::Counter(n, time) => {
-                println!("time: {}", ctx.now() - *time);
-                *time = ctx.now();
-                *n += 1;
-                ctx.post(tag.clone(), msg, 100);
-            }
-            State::Done => ctx.stop(tag),
-        }
-    }
+#[derive(Debug)]
+struct Ticks {
+    count: usize,
+    time: Millis,
 }
 
 fn main() {
     let mut sys = System::default();
     let mut ctx = sys.context();
     let tag = "tag".to_string();
-    ctx.bind(tag.clone(), State::Empty);
+    let fsm = FsmActor::new(State::Empty, Ticks { count: 0, time: 0 })
+        .on(State::Empty, |_, tag: &String, ctx, msg| {
+            ctx.schedule(tag.clone(), msg, Schedule::every(100).times(10));
+            Transition::Goto(State::Counter)
+        })
+        .on_enter(State::Counter, |ticks, _, ctx| ticks.time = ctx.now())
+        .on(State::Counter, |ticks, _, ctx, _| {
+            println!("time: {}", ctx.now() - ticks.time);
+            ticks.time = ctx.now();
+            ticks.count += 1;
+            match ticks.count {
+                10 => Transition::Goto(State::Done),
+                _ => Transition::Stay,
+            }
+        })
+        .timeout(State::Done, 100, Protocol::Empty)
+        .on(State::Done, |_, _, _, _| Transition::Stop)
+        .trace(|tag, from, to| println!("[tag={:?}] {:?} -> {:?}", tag, from, to));
+    ctx.bind(tag.clone(), fsm);
     ctx.send(&tag, Protocol::Empty);
     sys.run();
 }
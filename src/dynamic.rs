@@ -0,0 +1,254 @@
+use crate::{
+    dispatch, Action, Actor, ActorRef, Behaviour, Context, DeadLetter, Error, Factory,
+    FallibleActor, Mailbox, Message, Notify, Overflow, Priority, Reason, System, Tag,
+};
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+/// A system hosting actors of different types, each with its own message type.
+pub type DynSystem<T> = System<T, Dyn<T>, Packet>;
+
+trait Erased: Any + Send + Debug {
+    fn clone_box(&self) -> Box<dyn Erased>;
+    fn as_any(&self) -> &dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<M: Message> Erased for M {
+    fn clone_box(&self) -> Box<dyn Erased> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// Type-erased message of a `DynSystem`.
+#[derive(Debug)]
+pub struct Packet(Box<dyn Erased>);
+
+impl Packet {
+    pub fn new<M: Message>(msg: M) -> Self {
+        Self(Box::new(msg))
+    }
+
+    pub(crate) fn downcast_ref<M: Message>(&self) -> Option<&M> {
+        self.0.as_any().downcast_ref::<M>()
+    }
+
+    /// Recovers the original message, or returns the packet back if it holds
+    /// a message of another type.
+    pub fn downcast<M: Message>(self) -> Result<M, Self> {
+        if self.0.as_any().is::<M>() {
+            Ok(*self.0.into_any().downcast::<M>().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Clone for Packet {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+impl Message for Packet {}
+
+trait Hosted<T: Tag>: Send + Debug {
+    fn receive(
+        &mut self,
+        tag: &T,
+        ctx: &mut Context<T, Dyn<T>, Packet>,
+        msg: Packet,
+    ) -> Result<(), Error>;
+    fn start(&mut self, tag: &T, ctx: &mut Context<T, Dyn<T>, Packet>);
+    fn stop(&mut self, tag: &T, ctx: &mut Context<T, Dyn<T>, Packet>);
+    fn restart(&mut self, tag: &T, ctx: &mut Context<T, Dyn<T>, Packet>);
+}
+
+/// Hosted actor along with the behaviours it pushed, which cannot be kept by
+/// the hosting system as they take the actor's own message type.
+#[derive(Debug)]
+struct Host<A: Actor> {
+    actor: A,
+    behaviours: Vec<Behaviour<A::T, A, A::M>>,
+}
+
+impl<A: Actor> Host<A> {
+    /// Calls `f` with a typed context for the actor, keeping the behaviours
+    /// it pushes or pops and ending its turn if it unstashes.
+    fn with<R>(
+        &mut self,
+        ctx: &mut Context<A::T, Dyn<A::T>, Packet>,
+        f: impl FnOnce(&mut A, &mut Context<A::T, A, A::M>) -> R,
+    ) -> R {
+        let mut typed = ctx.map(pack::<A>);
+        typed.behaviours = std::mem::take(&mut self.behaviours);
+        let result = f(&mut self.actor, &mut typed);
+        self.behaviours = typed.behaviours;
+        ctx.unstashed |= typed.unstashed;
+        result
+    }
+}
+
+impl<A: Actor> Hosted<A::T> for Host<A> {
+    fn receive(
+        &mut self,
+        tag: &A::T,
+        ctx: &mut Context<A::T, Dyn<A::T>, Packet>,
+        msg: Packet,
+    ) -> Result<(), Error> {
+        // A packet of a foreign type can only be the result of a mistyped tag.
+        match msg.downcast::<A::M>() {
+            Ok(msg) => self.with(ctx, |actor, ctx| dispatch(actor, tag, ctx, msg)),
+            Err(msg) => {
+                ctx.dead_letter(tag, msg, Reason::Mistyped);
+                Ok(())
+            }
+        }
+    }
+
+    fn start(&mut self, tag: &A::T, ctx: &mut Context<A::T, Dyn<A::T>, Packet>) {
+        self.with(ctx, |actor, ctx| actor.on_start(tag, ctx));
+    }
+
+    fn stop(&mut self, tag: &A::T, ctx: &mut Context<A::T, Dyn<A::T>, Packet>) {
+        self.with(ctx, |actor, ctx| actor.on_stop(tag, ctx));
+    }
+
+    fn restart(&mut self, tag: &A::T, ctx: &mut Context<A::T, Dyn<A::T>, Packet>) {
+        self.with(ctx, |actor, ctx| actor.on_restart(tag, ctx));
+    }
+}
+
+/// Type-erased actor of a `DynSystem`. The wrapped actor keeps its own typed
+/// `Context`, whose actions are translated for the hosting system.
+#[derive(Debug)]
+pub struct Dyn<T: Tag>(Box<dyn Hosted<T>>);
+
+impl<T: Tag> Dyn<T> {
+    pub fn new<A: Actor<T = T>>(actor: A) -> Self {
+        Self(Box::new(Host {
+            actor,
+            behaviours: Vec::new(),
+        }))
+    }
+}
+
+impl<T: Tag> FallibleActor for Dyn<T> {
+    type T = T;
+    type M = Packet;
+
+    fn try_act(
+        &mut self,
+        tag: &T,
+        ctx: &mut Context<T, Self, Packet>,
+        msg: Packet,
+    ) -> Result<(), Error> {
+        self.0.receive(tag, ctx, msg)
+    }
+
+    fn on_start(&mut self, tag: &T, ctx: &mut Context<T, Self, Packet>) {
+        self.0.start(tag, ctx);
+    }
+
+    fn on_stop(&mut self, tag: &T, ctx: &mut Context<T, Self, Packet>) {
+        self.0.stop(tag, ctx);
+    }
+
+    fn on_restart(&mut self, tag: &T, ctx: &mut Context<T, Self, Packet>) {
+        self.0.restart(tag, ctx);
+    }
+}
+
+fn pack<A: Actor>(action: Action<A::T, A, A::M>) -> Action<A::T, Dyn<A::T>, Packet> {
+    match action {
+        Action::Bind(tag, actor, alive) => Action::Bind(tag, Dyn::new(actor), alive),
+        Action::Send(tag, msg, from) => Action::Send(tag, Packet::new(msg), from),
+        Action::Urgent(tag, msg) => Action::Urgent(tag, Packet::new(msg)),
+        Action::Reserved(tag, msg) => Action::Reserved(tag, Packet::new(msg)),
+        Action::Mailbox(tag, mailbox) => Action::Mailbox(tag, pack_mailbox(mailbox)),
+        Action::Post(id, tag, msg, millis) => Action::Post(id, tag, Packet::new(msg), millis),
+        Action::Schedule(id, tag, msg, schedule) => {
+            Action::Schedule(id, tag, Packet::new(msg), schedule)
+        }
+        Action::Cancel(id) => Action::Cancel(id),
+        Action::Reschedule(id, millis) => Action::Reschedule(id, millis),
+        Action::Ask(id, tag, msg, millis) => Action::Ask(id, tag, Packet::new(msg), millis),
+        Action::Reply(id, msg) => Action::Reply(id, Packet::new(msg)),
+        Action::Link(tag, parent, factory) => Action::Link(
+            tag,
+            parent,
+            factory.map(|factory| Factory::new(move || Dyn::new(factory.create()))),
+        ),
+        Action::Supervise(tag, supervision) => Action::Supervise(tag, supervision),
+        Action::Watch(watcher, tag, notify) => Action::Watch(
+            watcher,
+            tag,
+            Notify::new(move |terminated| Packet::new(notify.convert(terminated))),
+        ),
+        Action::Unwatch(watcher, tag) => Action::Unwatch(watcher, tag),
+        Action::Stash(tag, msg) => Action::Stash(tag, Packet::new(msg)),
+        Action::Unstash(tag) => Action::Unstash(tag),
+        Action::Stop(tag) => Action::Stop(tag),
+        Action::Shutdown(mode) => Action::Shutdown(mode),
+        Action::Dead(tag, msg, reason) => Action::Dead(tag, Packet::new(msg), reason),
+        Action::Error(tag, error) => Action::Error(tag, error),
+        // Behaviours of a hosted actor are kept by its `Dyn`, see `Host`.
+        Action::Done(tag, actor, msgs, _) => Action::Done(
+            tag,
+            actor.map(Dyn::new),
+            msgs.into_iter().map(Packet::new).collect(),
+            Vec::new(),
+        ),
+    }
+}
+
+fn pack_mailbox<T: Tag, M: Message>(mailbox: Mailbox<T, M>) -> Mailbox<T, Packet> {
+    let overflow = match mailbox.overflow {
+        Overflow::DropNewest => Overflow::DropNewest,
+        Overflow::DropOldest => Overflow::DropOldest,
+        Overflow::Reject(f) => Overflow::reject(move |letter: DeadLetter<T, Packet>| {
+            let DeadLetter { tag, msg, reason } = letter;
+            match msg.downcast::<M>() {
+                Ok(msg) => Packet::new(f(DeadLetter { tag, msg, reason })),
+                Err(msg) => msg,
+            }
+        }),
+        Overflow::Block => Overflow::Block,
+    };
+    let priority = mailbox.priority.map(|priority| -> Priority<Packet> {
+        Arc::new(move |msg: &Packet| msg.downcast_ref::<M>().map_or(0, |msg| priority(msg)))
+    });
+    Mailbox {
+        capacity: mailbox.capacity,
+        overflow,
+        priority,
+        stash: mailbox.stash,
+        gate: mailbox.gate,
+        dropped: mailbox.dropped,
+    }
+}
+
+impl<T: Tag> Context<T, Dyn<T>, Packet> {
+    /// Binds an actor of any type and returns a reference accepting its messages.
+    pub fn spawn<A: Actor<T = T>>(&mut self, tag: T, actor: A) -> ActorRef<T, A::M> {
+        self.map(pack::<A>).bind(tag, actor)
+    }
+
+    /// Binds an actor of any type with a bounded mailbox. Rejected messages are
+    /// delivered back to the sender in the message type of the receiver.
+    pub fn spawn_with<A: Actor<T = T>>(
+        &mut self,
+        tag: T,
+        actor: A,
+        mailbox: Mailbox<T, A::M>,
+    ) -> ActorRef<T, A::M> {
+        self.map(pack::<A>).bind_with(tag, actor, mailbox)
+    }
+}
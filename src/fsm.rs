@@ -0,0 +1,189 @@
+use crate::{Actor, Context, Message, Millis, Tag, TimerId};
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+/// Identifies a state of an `FsmActor`, usually a fieldless enum.
+pub trait FsmState: Sized + Eq + Hash + Debug + Clone + Send + 'static {}
+
+/// Context passed to the handlers and actions of an `FsmActor`.
+pub type FsmContext<T, S, D, M> = Context<T, FsmActor<T, S, D, M>, M>;
+
+/// What a state does after handling a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transition<S> {
+    Stay,
+    /// Leaves the state for the given one, re-entering it if it is the same.
+    Goto(S),
+    Stop,
+}
+
+type Handler<T, S, D, M> =
+    Box<dyn Fn(&mut D, &T, &mut FsmContext<T, S, D, M>, M) -> Transition<S> + Send + Sync>;
+type Hook<T, S, D, M> = Box<dyn Fn(&mut D, &T, &mut FsmContext<T, S, D, M>) + Send + Sync>;
+type Trace<T, S> = Box<dyn Fn(&T, &S, &S) + Send + Sync>;
+
+/// Everything declared for a single state.
+struct Spec<T: Tag, S: FsmState, D: Debug + Send + 'static, M: Message> {
+    handler: Option<Handler<T, S, D, M>>,
+    timeout: Option<(Millis, M)>,
+    enter: Option<Hook<T, S, D, M>>,
+    exit: Option<Hook<T, S, D, M>>,
+}
+
+impl<T: Tag, S: FsmState, D: Debug + Send + 'static, M: Message> Default for Spec<T, S, D, M> {
+    fn default() -> Self {
+        Self {
+            handler: None,
+            timeout: None,
+            enter: None,
+            exit: None,
+        }
+    }
+}
+
+/// Actor declared as a finite state machine over its data `D`: each state
+/// handles messages with its own handler, which decides the transition. Entry
+/// and exit actions run whenever a state is entered or left, including the
+/// initial state once the actor starts and the last one once it stops.
+/// Messages a state has no handler for are ignored.
+///
+/// A state with a timeout posts its message to the actor once it has been in
+/// the state for the given millis, and cancels it when the state is left. A
+/// timeout that fell due just before the state was left may still arrive.
+pub struct FsmActor<T: Tag, S: FsmState, D: Debug + Send + 'static, M: Message> {
+    state: S,
+    data: D,
+    states: HashMap<S, Spec<T, S, D, M>>,
+    timer: Option<TimerId>,
+    trace: Option<Trace<T, S>>,
+}
+
+impl<T: Tag, S: FsmState, D: Debug + Send + 'static, M: Message> FsmActor<T, S, D, M> {
+    pub fn new(initial: S, data: D) -> Self {
+        Self {
+            state: initial,
+            data,
+            states: HashMap::new(),
+            timer: None,
+            trace: None,
+        }
+    }
+
+    /// Handles the messages received in `state` with `handler`.
+    pub fn on<F>(mut self, state: S, handler: F) -> Self
+    where
+        F: Fn(&mut D, &T, &mut FsmContext<T, S, D, M>, M) -> Transition<S> + Send + Sync + 'static,
+    {
+        self.states.entry(state).or_default().handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Delivers `msg` once the actor has been in `state` for `millis`.
+    pub fn timeout(mut self, state: S, millis: Millis, msg: M) -> Self {
+        self.states.entry(state).or_default().timeout = Some((millis, msg));
+        self
+    }
+
+    pub fn on_enter<F>(mut self, state: S, action: F) -> Self
+    where
+        F: Fn(&mut D, &T, &mut FsmContext<T, S, D, M>) + Send + Sync + 'static,
+    {
+        self.states.entry(state).or_default().enter = Some(Box::new(action));
+        self
+    }
+
+    pub fn on_exit<F>(mut self, state: S, action: F) -> Self
+    where
+        F: Fn(&mut D, &T, &mut FsmContext<T, S, D, M>) + Send + Sync + 'static,
+    {
+        self.states.entry(state).or_default().exit = Some(Box::new(action));
+        self
+    }
+
+    /// Calls `f` with the tag, the state left and the state entered on every
+    /// transition.
+    pub fn trace<F: Fn(&T, &S, &S) + Send + Sync + 'static>(self, f: F) -> Self {
+        Self {
+            trace: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn data(&self) -> &D {
+        &self.data
+    }
+
+    fn enter(&mut self, tag: &T, ctx: &mut FsmContext<T, S, D, M>) {
+        let spec = match self.states.get(&self.state) {
+            Some(spec) => spec,
+            None => return,
+        };
+        if let Some(enter) = &spec.enter {
+            enter(&mut self.data, tag, ctx);
+        }
+        if let Some((millis, msg)) = &spec.timeout {
+            self.timer = Some(ctx.post(tag.clone(), msg.clone(), *millis));
+        }
+    }
+
+    fn exit(&mut self, tag: &T, ctx: &mut FsmContext<T, S, D, M>) {
+        if let Some(id) = self.timer.take() {
+            ctx.cancel(id);
+        }
+        if let Some(exit) = self
+            .states
+            .get(&self.state)
+            .and_then(|spec| spec.exit.as_ref())
+        {
+            exit(&mut self.data, tag, ctx);
+        }
+    }
+}
+
+impl<T: Tag, S: FsmState, D: Debug + Send + 'static, M: Message> Debug for FsmActor<T, S, D, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FsmActor")
+            .field("state", &self.state)
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+impl<T: Tag, S: FsmState, D: Debug + Send + 'static, M: Message> Actor for FsmActor<T, S, D, M> {
+    type T = T;
+    type M = M;
+
+    fn act(&mut self, tag: &T, ctx: &mut FsmContext<T, S, D, M>, msg: M) {
+        let handler = self
+            .states
+            .get(&self.state)
+            .and_then(|spec| spec.handler.as_ref());
+        let transition = match handler {
+            Some(handler) => handler(&mut self.data, tag, ctx, msg),
+            None => Transition::Stay,
+        };
+        match transition {
+            Transition::Stay => (),
+            Transition::Goto(next) => {
+                self.exit(tag, ctx);
+                if let Some(trace) = &self.trace {
+                    trace(tag, &self.state, &next);
+                }
+                self.state = next;
+                self.enter(tag, ctx);
+            }
+            Transition::Stop => ctx.stop(tag),
+        }
+    }
+
+    fn on_start(&mut self, tag: &T, ctx: &mut FsmContext<T, S, D, M>) {
+        self.enter(tag, ctx);
+    }
+
+    fn on_stop(&mut self, tag: &T, ctx: &mut FsmContext<T, S, D, M>) {
+        self.exit(tag, ctx);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{
+    get_current_millis, handle_actions, next_id, Actor, Message, Outcome, ReplyTo, Signal, System,
+    Tag,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+/// Messages processed before the runner yields to other tasks.
+const BUDGET: usize = 1024;
+
+impl<T: Tag, A: Actor<T = T, M = M>, M: Message> System<T, A, M> {
+    /// Runs the system as a future on wall-clock time until all actors stop or
+    /// it is shut down, without blocking the thread of the executor. When no
+    /// mailbox has work, the future waits until either a new action arrives or
+    /// the next posted deadline is reached, using `sleep` of the async runtime,
+    /// e.g. `tokio::time::sleep`.
+    pub async fn run_async<S, F>(&mut self, mut sleep: S) -> Outcome<T, M>
+    where
+        S: FnMut(Duration) -> F,
+        F: Future<Output = ()>,
+    {
+        loop {
+            let now = get_current_millis();
+            let tick = self.tick(now, BUDGET);
+            if tick.done {
+                break;
+            }
+            if tick.idle {
+                let sleep = tick
+                    .next_deadline
+                    .map(|deadline| Box::pin(sleep(Duration::from_millis(deadline - now))));
+                Wait::new(&self.signal, sleep).await;
+            } else {
+                YieldNow(false).await;
+            }
+        }
+        while !self.running.is_empty() {
+            Wait::<F>::new(&self.signal, None).await;
+            handle_actions(self);
+        }
+        self.finish()
+    }
+}
+
+/// Completes once an action arrives or the sleep, if any, completes.
+struct Wait<'a, F> {
+    signal: &'a Signal,
+    sleep: Option<Pin<Box<F>>>,
+}
+
+impl<'a, F> Wait<'a, F> {
+    fn new(signal: &'a Signal, sleep: Option<Pin<Box<F>>>) -> Self {
+        Self { signal, sleep }
+    }
+}
+
+impl<F: Future<Output = ()>> Future for Wait<'_, F> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.signal.take() {
+            return Poll::Ready(());
+        }
+        self.signal.register(cx.waker());
+        // An action may have arrived before the waker was registered.
+        if self.signal.take() {
+            return Poll::Ready(());
+        }
+        match &mut self.sleep {
+            Some(sleep) => sleep.as_mut().poll(cx),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Lets other tasks of the executor run before the loop continues.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[derive(Debug)]
+struct Slot<M> {
+    reply: Option<M>,
+    replied: bool,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl<M> Slot<M> {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Closes the slot once every `ReplyTo` of the request is dropped.
+struct Closer<M>(Arc<Mutex<Slot<M>>>);
+
+impl<M> Drop for Closer<M> {
+    fn drop(&mut self) {
+        let mut slot = self.0.lock().unwrap();
+        slot.closed = true;
+        slot.wake();
+    }
+}
+
+/// Future resolving to the reply to a request made from async code, or to
+/// `None` if the request was dropped without a reply.
+#[derive(Debug)]
+pub struct Response<M: Message>(Arc<Mutex<Slot<M>>>);
+
+impl<M: Message> Future for Response<M> {
+    type Output = Option<M>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<M>> {
+        let mut slot = self.0.lock().unwrap();
+        if let Some(reply) = slot.reply.take() {
+            return Poll::Ready(Some(reply));
+        }
+        if slot.closed || slot.replied {
+            return Poll::Ready(None);
+        }
+        slot.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<M: Message> ReplyTo<M> {
+    /// Creates a handle to be sent to an actor along with a request from
+    /// outside the system, and the future that resolves to its first reply.
+    pub fn channel() -> (Self, Response<M>) {
+        let slot = Arc::new(Mutex::new(Slot {
+            reply: None,
+            replied: false,
+            closed: false,
+            waker: None,
+        }));
+        let closer = Closer(slot.clone());
+        let reply = ReplyTo {
+            id: next_id(),
+            route: Arc::new(move |msg| {
+                let mut slot = closer.0.lock().unwrap();
+                if !slot.replied {
+                    slot.replied = true;
+                    slot.reply = Some(msg);
+                    slot.wake();
+                }
+            }),
+        };
+        (reply, Response(slot))
+    }
+}
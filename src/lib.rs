@@ -1,59 +1,481 @@
+mod dynamic;
+mod fsm;
+#[cfg(feature = "async")]
+mod future;
+mod mailbox;
+mod pool;
+
+pub use dynamic::{Dyn, DynSystem, Packet};
+pub use fsm::{FsmActor, FsmContext, FsmState, Transition};
+#[cfg(feature = "async")]
+pub use future::Response;
+use mailbox::{Gate, Queue, STASH_CAPACITY};
+pub use mailbox::{Mailbox, Overflow, Priority};
+use pool::{Job, Pool};
 use std::{
-    collections::{BinaryHeap, HashMap, VecDeque},
+    cell::Cell,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
     fmt::Debug,
     hash::Hash,
-    sync::mpsc::{channel, Receiver, Sender},
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    task::Waker,
     time::{Duration, SystemTime},
 };
 
-pub trait Message: Sized + Debug + Clone + 'static {}
+pub trait Message: Sized + Debug + Clone + Send + 'static {}
 
-pub trait Tag: Sized + Eq + Hash + Debug + Clone + 'static {}
+pub trait Tag: Sized + Eq + Hash + Debug + Clone + Send + 'static {}
 
 impl Tag for String {}
 
 pub type Millis = u64;
 
-pub trait Actor: Sized + Debug {
+/// Error returned by a fallible actor, see `FallibleActor`.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+pub trait Actor: Sized + Debug + Send + 'static {
     type T: Tag;
     type M: Message;
+
+    /// Handles a message. Actors that can fail implement `FallibleActor`
+    /// instead.
     fn act(&mut self, tag: &Self::T, ctx: &mut Context<Self::T, Self, Self::M>, msg: Self::M);
+
+    /// Handles a message, an error is dealt with as set by the `ErrorPolicy`
+    /// of the system. Calls `act` unless implemented.
+    fn try_act(
+        &mut self,
+        tag: &Self::T,
+        ctx: &mut Context<Self::T, Self, Self::M>,
+        msg: Self::M,
+    ) -> Result<(), Error> {
+        self.act(tag, ctx, msg);
+        Ok(())
+    }
+
+    /// Called once the actor is bound to the tag.
+    fn on_start(&mut self, _tag: &Self::T, _ctx: &mut Context<Self::T, Self, Self::M>) {}
+
+    /// Called once the actor is stopped or replaced by a rebind of the tag,
+    /// before the messages left in its mailbox are turned into dead letters.
+    fn on_stop(&mut self, _tag: &Self::T, _ctx: &mut Context<Self::T, Self, Self::M>) {}
+
+    /// Called instead of `on_start` when the actor replaces another one bound
+    /// to the same tag.
+    fn on_restart(&mut self, tag: &Self::T, ctx: &mut Context<Self::T, Self, Self::M>) {
+        self.on_start(tag, ctx);
+    }
+}
+
+/// Actor whose handler can fail, an error is dealt with as set by the
+/// `ErrorPolicy` of the system. Every `FallibleActor` is an `Actor`.
+pub trait FallibleActor: Sized + Debug + Send + 'static {
+    type T: Tag;
+    type M: Message;
+
+    fn try_act(
+        &mut self,
+        tag: &Self::T,
+        ctx: &mut Context<Self::T, Self, Self::M>,
+        msg: Self::M,
+    ) -> Result<(), Error>;
+
+    /// See `Actor::on_start`.
+    fn on_start(&mut self, _tag: &Self::T, _ctx: &mut Context<Self::T, Self, Self::M>) {}
+
+    /// See `Actor::on_stop`.
+    fn on_stop(&mut self, _tag: &Self::T, _ctx: &mut Context<Self::T, Self, Self::M>) {}
+
+    /// See `Actor::on_restart`.
+    fn on_restart(&mut self, tag: &Self::T, ctx: &mut Context<Self::T, Self, Self::M>) {
+        FallibleActor::on_start(self, tag, ctx);
+    }
+}
+
+impl<F: FallibleActor> Actor for F {
+    type T = F::T;
+    type M = F::M;
+
+    /// Only called when used directly, as the system calls `try_act`. The
+    /// error cannot be returned, so it is raised as a panic.
+    fn act(&mut self, tag: &F::T, ctx: &mut Context<F::T, F, F::M>, msg: F::M) {
+        if let Err(error) = FallibleActor::try_act(self, tag, ctx, msg) {
+            panic!("{error}");
+        }
+    }
+
+    fn try_act(
+        &mut self,
+        tag: &F::T,
+        ctx: &mut Context<F::T, F, F::M>,
+        msg: F::M,
+    ) -> Result<(), Error> {
+        FallibleActor::try_act(self, tag, ctx, msg)
+    }
+
+    fn on_start(&mut self, tag: &F::T, ctx: &mut Context<F::T, F, F::M>) {
+        FallibleActor::on_start(self, tag, ctx);
+    }
+
+    fn on_stop(&mut self, tag: &F::T, ctx: &mut Context<F::T, F, F::M>) {
+        FallibleActor::on_stop(self, tag, ctx);
+    }
+
+    fn on_restart(&mut self, tag: &F::T, ctx: &mut Context<F::T, F, F::M>) {
+        FallibleActor::on_restart(self, tag, ctx);
+    }
 }
 
+/// Passed to actors to act on the system they run in. Outside of actors, use
+/// a `SystemHandle` instead.
 pub struct Context<T: Tag, A: Actor, M: Message> {
-    tx: Sender<Action<T, A, M>>,
+    tx: Sink<T, A, M>,
     now: Millis,
+    /// Tag of the actor being run, it becomes the parent of actors it binds.
+    tag: Option<T>,
+    /// Behaviours pushed by the actor being run, the last one takes its
+    /// messages.
+    behaviours: Vec<Behaviour<T, A, M>>,
+    /// Set once the actor being run unstashes its messages, which ends its
+    /// turn.
+    unstashed: bool,
+    gates: Gates<T>,
+}
+
+/// Handler taking the messages of an actor in place of `try_act`, see
+/// `Context::push_behaviour`.
+pub type Behaviour<T, A, M> = fn(&mut A, &T, &mut Context<T, A, M>, M) -> Result<(), Error>;
+
+/// Destination of the actions produced through a `Context`: either the channel
+/// of the system, or an adapter translating them for an enclosing system.
+pub(crate) struct Sink<T: Tag, A: Actor, M: Message>(Arc<dyn Fn(Action<T, A, M>) + Send + Sync>);
+
+impl<T: Tag, A: Actor, M: Message> Sink<T, A, M> {
+    fn send(&self, action: Action<T, A, M>) {
+        (self.0)(action)
+    }
+}
+
+impl<T: Tag, A: Actor, M: Message> Clone for Sink<T, A, M> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Tag, A: Actor, M: Message> Sink<T, A, M> {
+    /// Sends actions into the channel of a system, notifying its signal.
+    /// Actions sent once the system has been dropped are discarded.
+    fn channel(tx: Sender<Action<T, A, M>>, signal: Arc<Signal>) -> Self {
+        Self(Arc::new(move |action| {
+            if tx.send(action).is_ok() {
+                signal.notify();
+            }
+        }))
+    }
+}
+
+/// Tells a system waiting for actions that one has arrived. Costs a single
+/// atomic operation per action unless a waker is registered.
+#[derive(Debug, Default)]
+pub(crate) struct Signal {
+    notified: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Signal {
+    pub(crate) fn notify(&self) {
+        if !self.notified.swap(true, Ordering::AcqRel) {
+            if let Some(waker) = self.waker.lock().unwrap().take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Returns `true` if an action arrived since the last call.
+    #[cfg_attr(not(feature = "async"), allow(dead_code))]
+    pub(crate) fn take(&self) -> bool {
+        self.notified.swap(false, Ordering::AcqRel)
+    }
+
+    /// Registers the waker to be woken by the next action.
+    #[cfg_attr(not(feature = "async"), allow(dead_code))]
+    pub(crate) fn register(&self, waker: &Waker) {
+        *self.waker.lock().unwrap() = Some(waker.clone());
+    }
 }
 
 #[derive(Debug)]
 pub enum Action<T: Tag, A: Actor, M: Message> {
-    Bind(T, A),
-    Send(T, M),
-    Post(T, M, Millis),
+    /// Binds the actor to the tag, the flag is cleared once the actor stops.
+    Bind(T, A, Arc<AtomicBool>),
+    /// Sends the message to the tag, from the actor bound to the second tag
+    /// if it was sent by one.
+    Send(T, M, Option<T>),
+    /// Sends the message to the tag ahead of all other messages in its mailbox.
+    Urgent(T, M),
+    /// Sends a message for which a blocked sender has already taken room in
+    /// the mailbox of the tag.
+    Reserved(T, M),
+    /// Bounds the mailbox of the tag.
+    Mailbox(T, Mailbox<T, M>),
+    /// Keeps the message aside until the tag unstashes its messages.
+    Stash(T, M),
+    Unstash(T),
+    Post(TimerId, T, M, Millis),
+    Schedule(TimerId, T, M, Schedule),
+    Cancel(TimerId),
+    Reschedule(TimerId, Millis),
+    /// Registers a pending request of the tag, the message is delivered to it
+    /// if no reply arrives within the given millis.
+    Ask(u64, T, M, Millis),
+    Reply(u64, M),
+    /// Makes the tag a child of the parent tag, if any. The factory, if any,
+    /// is used to recreate the actor after a failure.
+    Link(T, Option<T>, Option<Factory<A>>),
+    /// Sets how the tag supervises its children.
+    Supervise(T, Supervision),
+    /// Makes the first tag watch the actor bound to the second one.
+    Watch(T, T, Notify<T, M>),
+    Unwatch(T, T),
     Stop(T),
+    Shutdown(Shutdown),
+    /// Turns a message sent to the tag into a dead letter.
+    Dead(T, M, Reason),
+    /// Returned by a worker thread once the actor has processed its messages,
+    /// `None` if `act` panicked, along with the messages it did not get to and
+    /// its behaviours.
+    Done(T, Option<A>, Vec<M>, Vec<Behaviour<T, A, M>>),
+    /// Returned by a worker thread after the actor, if its handler returned
+    /// an error.
+    Error(T, Error),
 }
 
 impl<T: Tag, A: Actor, M: Message> Context<T, A, M> {
-    fn new(tx: Sender<Action<T, A, M>>) -> Self {
-        Self { tx, now: 0 }
+    fn new(tx: Sink<T, A, M>, gates: Gates<T>) -> Self {
+        Self {
+            tx,
+            now: 0,
+            tag: None,
+            behaviours: Vec::new(),
+            unstashed: false,
+            gates,
+        }
+    }
+
+    /// Creates a context for actors of another type, whose actions are
+    /// translated by `f` before reaching this context's destination.
+    fn map<B: Actor, N: Message>(
+        &self,
+        f: impl Fn(Action<T, B, N>) -> Action<T, A, M> + Send + Sync + 'static,
+    ) -> Context<T, B, N> {
+        let tx = self.tx.clone();
+        Context {
+            tx: Sink(Arc::new(move |action| tx.send(f(action)))),
+            now: self.now,
+            tag: self.tag.clone(),
+            behaviours: Vec::new(),
+            unstashed: false,
+            gates: self.gates.clone(),
+        }
     }
 }
 
 impl<T: Tag, A: Actor, M: Message> Context<T, A, M> {
     pub fn stop(&mut self, tag: &T) {
-        self.tx.send(Action::Stop(tag.clone())).unwrap();
+        self.tx.send(Action::Stop(tag.clone()));
+    }
+
+    pub(crate) fn dead_letter(&mut self, tag: &T, msg: M, reason: Reason) {
+        self.tx.send(Action::Dead(tag.clone(), msg, reason));
     }
 
     pub fn send(&mut self, tag: &T, msg: M) {
-        self.tx.send(Action::Send(tag.clone(), msg)).unwrap();
+        self.tx
+            .send(Action::Send(tag.clone(), msg, self.tag.clone()));
+    }
+
+    /// Sends the message ahead of all messages waiting in the mailbox of the
+    /// tag, bypassing its bounds. Meant for control messages.
+    pub fn send_urgent(&mut self, tag: &T, msg: M) {
+        self.tx.send(Action::Urgent(tag.clone(), msg));
+    }
+
+    /// Binds the actor to the tag. When called from a running actor, the new
+    /// actor becomes its child: it is stopped together with the parent, and a
+    /// failure of the child stops it without restarting.
+    pub fn bind(&mut self, tag: T, actor: A) -> ActorRef<T, M> {
+        self.bind_linked(tag, actor, None)
+    }
+
+    /// Binds the actor to the tag with a configured mailbox. The configuration
+    /// stays in place across rebinds of the tag until the actor is stopped.
+    pub fn bind_with(&mut self, tag: T, actor: A, mut mailbox: Mailbox<T, M>) -> ActorRef<T, M> {
+        let mut actor_ref = self.bind(tag.clone(), actor);
+        if let (Some(capacity), Overflow::Block) = (mailbox.capacity, &mailbox.overflow) {
+            let gate = Arc::new(Gate::new(capacity));
+            mailbox.gate = Some(gate.clone());
+            actor_ref.gate = Some(gate);
+        }
+        self.tx.send(Action::Mailbox(tag, mailbox));
+        actor_ref
+    }
+
+    /// Binds the actor created by `factory` to the tag. After a failure the
+    /// actor is recreated by calling `factory` again, as decided by the
+    /// supervision of its parent.
+    pub fn supervise<F: Fn() -> A + Send + Sync + 'static>(
+        &mut self,
+        tag: T,
+        factory: F,
+    ) -> ActorRef<T, M> {
+        let factory = Factory(Arc::new(factory));
+        self.bind_linked(tag, factory.create(), Some(factory))
+    }
+
+    /// Sets how the actor bound to the tag supervises its children.
+    pub fn set_supervision(&mut self, tag: &T, supervision: Supervision) {
+        self.tx.send(Action::Supervise(tag.clone(), supervision));
+    }
+
+    /// Handles the next messages of the actor being run with `behaviour`
+    /// instead of `try_act`, until it is popped. Behaviours are dropped once
+    /// the actor is stopped or replaced.
+    pub fn push_behaviour(&mut self, behaviour: Behaviour<T, A, M>) {
+        self.behaviours.push(behaviour);
+    }
+
+    /// Goes back to the behaviour that was in place before the last push.
+    /// Returns `false` if none was pushed.
+    pub fn pop_behaviour(&mut self) -> bool {
+        self.behaviours.pop().is_some()
+    }
+
+    /// Keeps the message aside until the actor being run calls `unstash_all`,
+    /// e.g. one it is not ready for in its current state. Messages beyond the
+    /// stash capacity of its mailbox become dead letters. Has no effect
+    /// outside of actors.
+    pub fn stash(&mut self, msg: M) {
+        if let Some(tag) = &self.tag {
+            self.tx.send(Action::Stash(tag.clone(), msg));
+        }
+    }
+
+    /// Puts the stashed messages of the actor being run back ahead of those
+    /// waiting in its mailbox, in the order they were stashed. Ends the turn
+    /// of the actor, so that they are the next messages it handles.
+    pub fn unstash_all(&mut self) {
+        if let Some(tag) = &self.tag {
+            self.tx.send(Action::Unstash(tag.clone()));
+            self.unstashed = true;
+        }
+    }
+
+    /// Makes the actor being run watch the actor bound to the tag. Once that
+    /// actor stops, fails or is replaced, the watcher receives the message
+    /// built by `notify` ahead of all other messages, and the watch ends. An
+    /// unbound tag is reported right away. Has no effect outside of actors.
+    pub fn watch<F: Fn(Terminated<T>) -> M + Send + Sync + 'static>(&mut self, tag: &T, notify: F) {
+        if let Some(watcher) = &self.tag {
+            self.tx.send(Action::Watch(
+                watcher.clone(),
+                tag.clone(),
+                Notify::new(notify),
+            ));
+        }
+    }
+
+    /// Ends the watch of the actor being run on the tag, if any.
+    pub fn unwatch(&mut self, tag: &T) {
+        if let Some(watcher) = &self.tag {
+            self.tx.send(Action::Unwatch(watcher.clone(), tag.clone()));
+        }
+    }
+
+    fn bind_linked(&mut self, tag: T, actor: A, factory: Option<Factory<A>>) -> ActorRef<T, M> {
+        let alive = Arc::new(AtomicBool::new(true));
+        self.tx
+            .send(Action::Bind(tag.clone(), actor, alive.clone()));
+        if self.tag.is_some() || factory.is_some() {
+            self.tx
+                .send(Action::Link(tag.clone(), self.tag.clone(), factory));
+        }
+        ActorRef {
+            tag,
+            route: Arc::new(self.tx.clone()),
+            alive,
+            gate: None,
+            gates: self.gates.clone(),
+        }
+    }
+
+    pub fn post(&mut self, tag: T, msg: M, millis: Millis) -> TimerId {
+        let id = TimerId(next_id());
+        self.tx.send(Action::Post(id, tag, msg, millis));
+        id
     }
 
-    pub fn bind(&mut self, tag: T, actor: A) {
-        self.tx.send(Action::Bind(tag, actor)).unwrap();
+    /// Posts the message to the tag repeatedly, as described by `schedule`.
+    /// The returned id cancels all remaining firings.
+    pub fn schedule(&mut self, tag: T, msg: M, schedule: Schedule) -> TimerId {
+        let id = TimerId(next_id());
+        self.tx.send(Action::Schedule(id, tag, msg, schedule));
+        id
     }
 
-    pub fn post(&mut self, tag: T, msg: M, millis: Millis) {
-        self.tx.send(Action::Post(tag, msg, millis)).unwrap();
+    /// Posts the message to the tag every `period` millis, starting one period
+    /// from now.
+    pub fn schedule_every(&mut self, tag: T, msg: M, period: Millis) -> TimerId {
+        self.schedule(tag, msg, Schedule::every(period))
+    }
+
+    /// Withdraws a posted message, unless it has already fired.
+    pub fn cancel(&mut self, id: TimerId) {
+        self.tx.send(Action::Cancel(id));
+    }
+
+    /// Moves a posted message that has not fired yet to `millis` from now. A
+    /// recurring message keeps its period, counted from the new deadline.
+    pub fn reschedule(&mut self, id: TimerId, millis: Millis) {
+        self.tx.send(Action::Reschedule(id, millis));
+    }
+
+    /// Sends the request built by `request` to `to`, the reply is delivered to
+    /// `from` as a regular message. If no reply arrives within `timeout` millis,
+    /// `expired` is delivered instead and any late reply is dropped.
+    pub fn ask<F: FnOnce(ReplyTo<M>) -> M>(
+        &mut self,
+        from: &T,
+        to: &T,
+        request: F,
+        timeout: Millis,
+        expired: M,
+    ) {
+        let reply = self.reply_to(from, timeout, expired);
+        self.send(to, request(reply));
+    }
+
+    /// Registers a pending request of `from` and returns the handle to answer
+    /// it with. Allows sending the request to an actor of another message type
+    /// through its `ActorRef`.
+    pub fn reply_to(&mut self, from: &T, timeout: Millis, expired: M) -> ReplyTo<M> {
+        let id = next_id();
+        self.tx
+            .send(Action::Ask(id, from.clone(), expired, timeout));
+        let tx = self.tx.clone();
+        ReplyTo {
+            id,
+            route: Arc::new(move |msg| tx.send(Action::Reply(id, msg))),
+        }
+    }
+
+    /// Asks the system to stop running, see `Shutdown`.
+    pub fn shutdown(&mut self, mode: Shutdown) {
+        self.tx.send(Action::Shutdown(mode));
     }
 
     pub fn now(&self) -> Millis {
@@ -61,143 +483,1929 @@ impl<T: Tag, A: Actor, M: Message> Context<T, A, M> {
     }
 }
 
-struct Post<T: Tag, M: Message>(Millis, T, M);
+fn next_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Handle answering a request made with `Context::ask`. Only the first reply
+/// to a request that has not expired is delivered.
+pub struct ReplyTo<M: Message> {
+    id: u64,
+    route: Arc<dyn Fn(M) + Send + Sync>,
+}
 
-impl<T: Tag, M: Message> PartialEq for Post<T, M> {
-    fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0
+impl<M: Message> ReplyTo<M> {
+    pub fn reply(self, msg: M) {
+        (self.route)(msg)
     }
 }
 
-impl<T: Tag, M: Message> Eq for Post<T, M> {}
+impl<M: Message> Clone for ReplyTo<M> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            route: self.route.clone(),
+        }
+    }
+}
 
-impl<T: Tag, M: Message> PartialOrd for Post<T, M> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl<M: Message> Debug for ReplyTo<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ReplyTo").field(&self.id).finish()
     }
 }
 
-impl<T: Tag, M: Message> Ord for Post<T, M> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.0.cmp(&other.0)
+trait Route<T: Tag, M: Message>: Send + Sync {
+    fn send(&self, tag: &T, msg: M);
+    fn reserved(&self, tag: &T, msg: M);
+    fn urgent(&self, tag: &T, msg: M);
+    fn post(&self, tag: &T, msg: M, millis: Millis) -> TimerId;
+}
+
+impl<T: Tag, A: Actor, M: Message> Route<T, M> for Sink<T, A, M> {
+    fn send(&self, tag: &T, msg: M) {
+        Sink::send(self, Action::Send(tag.clone(), msg, None));
+    }
+
+    fn reserved(&self, tag: &T, msg: M) {
+        Sink::send(self, Action::Reserved(tag.clone(), msg));
+    }
+
+    fn urgent(&self, tag: &T, msg: M) {
+        Sink::send(self, Action::Urgent(tag.clone(), msg));
+    }
+
+    fn post(&self, tag: &T, msg: M, millis: Millis) -> TimerId {
+        let id = TimerId(next_id());
+        Sink::send(self, Action::Post(id, tag.clone(), msg, millis));
+        id
     }
 }
 
-pub struct System<T: Tag, A: Actor, M: Message> {
-    actors: HashMap<T, A>,
-    queues: HashMap<T, VecDeque<M>>,
-    posted: BinaryHeap<Post<T, M>>,
-    millis: Millis,
-    tx: Sender<Action<T, A, M>>,
-    rx: Receiver<Action<T, A, M>>,
+/// Cheap cloneable reference to a bound actor accepting messages of type `M`.
+pub struct ActorRef<T: Tag, M: Message> {
+    tag: T,
+    route: Arc<dyn Route<T, M>>,
+    alive: Arc<AtomicBool>,
+    /// Room in the mailbox set up by `bind_with`, until the system applies
+    /// it. Later on the gate of the tag is looked up, as it is shared by all
+    /// references to the actors bound to the tag.
+    gate: Option<Arc<Gate>>,
+    gates: Gates<T>,
 }
 
-impl<T: Tag, A: Actor, M: Message> Default for System<T, A, M> {
-    fn default() -> Self {
-        let (tx, rx) = channel();
+impl<T: Tag, M: Message> ActorRef<T, M> {
+    pub fn tag(&self) -> &T {
+        &self.tag
+    }
+
+    /// Returns `false` once the actor has been stopped or replaced by a rebind.
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::Acquire)
+    }
+
+    /// Sends the message to the actor. If its mailbox blocks when full, waits
+    /// for room unless called from within the system.
+    pub fn send(&self, msg: M) {
+        let gate = self.gates.lock().unwrap().get(&self.tag).cloned();
+        match gate.or_else(|| self.gate.clone()) {
+            Some(gate) if !Inside::is_set() && gate.acquire() => {
+                self.route.reserved(&self.tag, msg)
+            }
+            _ => self.route.send(&self.tag, msg),
+        }
+    }
+
+    /// Sends the message ahead of all messages waiting in the mailbox, never
+    /// blocking. See `Context::send_urgent`.
+    pub fn send_urgent(&self, msg: M) {
+        self.route.urgent(&self.tag, msg);
+    }
+
+    pub fn post(&self, msg: M, millis: Millis) -> TimerId {
+        self.route.post(&self.tag, msg, millis)
+    }
+}
+
+impl<T: Tag, M: Message> Clone for ActorRef<T, M> {
+    fn clone(&self) -> Self {
         Self {
-            actors: Default::default(),
-            queues: Default::default(),
-            posted: Default::default(),
-            millis: 0,
-            tx,
-            rx,
+            tag: self.tag.clone(),
+            route: self.route.clone(),
+            alive: self.alive.clone(),
+            gate: self.gate.clone(),
+            gates: self.gates.clone(),
         }
     }
 }
 
-impl<T: Tag, A: Actor<T = T, M = M>, M: Message> System<T, A, M> {
-    pub fn context(&self) -> Context<T, A, M> {
-        Context::new(self.tx.clone())
+impl<T: Tag, M: Message> Debug for ActorRef<T, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActorRef")
+            .field("tag", &self.tag)
+            .field("alive", &self.is_alive())
+            .finish()
+    }
+}
+
+/// Identifies a posted message, so that it can be cancelled or rescheduled
+/// before it fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+/// When a recurring message fires: after `delay`, then every `period` millis,
+/// each firing shifted by up to `jitter` millis, at most `times` times.
+/// Built from `Schedule::every`, which keeps the period above zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    period: Millis,
+    delay: Millis,
+    jitter: Millis,
+    times: Option<u64>,
+}
+
+impl Schedule {
+    /// Fires every `period` millis, starting one period from now.
+    pub fn every(period: Millis) -> Self {
+        Self {
+            period: period.max(1),
+            delay: period,
+            jitter: 0,
+            times: None,
+        }
+    }
+
+    pub fn delay(self, delay: Millis) -> Self {
+        Self { delay, ..self }
+    }
+
+    pub fn jitter(self, jitter: Millis) -> Self {
+        Self { jitter, ..self }
     }
 
-    pub fn run(&mut self) {
-        action_loop(self, get_current_millis);
+    pub fn times(self, times: u64) -> Self {
+        Self {
+            times: Some(times),
+            ..self
+        }
     }
 }
 
-fn get_current_millis() -> Millis {
-    SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as Millis
+/// Progress of a recurring timer. Deadlines are counted from `origin` rather
+/// than from the previous firing, so that they do not drift.
+struct Repeat {
+    schedule: Schedule,
+    origin: Millis,
+    index: u64,
+    fired: u64,
+    /// Taken from the system rather than the process-wide timer ids, so that
+    /// the jitter repeats from one run to the next.
+    seed: u64,
 }
 
-fn handle_actions<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>) {
-    while let Ok(action) = sys.rx.recv_timeout(Duration::from_millis(0)) {
-        match action {
-            Action::Bind(tag, actor) => {
-                sys.actors.insert(tag, actor);
-            }
-            Action::Send(tag, msg) => {
-                sys.queues.entry(tag).or_default().push_back(msg);
-            }
-            Action::Post(tag, msg, mut millis) => {
-                millis += sys.millis;
-                sys.posted.push(Post(millis, tag, msg));
-            }
-            Action::Stop(tag) => {
-                sys.actors.remove(&tag);
-            }
+impl Repeat {
+    /// Returns `None` once the deadline lies beyond the last representable
+    /// millisecond, which ends the timer.
+    fn deadline(&self) -> Option<Millis> {
+        let jitter = match self.schedule.jitter {
+            0 => 0,
+            max => splitmix64(self.seed ^ self.index.rotate_left(32)) % max.saturating_add(1),
+        };
+        self.index
+            .checked_mul(self.schedule.period)?
+            .checked_add(self.origin)?
+            .checked_add(jitter)
+    }
+}
+
+/// Deterministic pseudo-random jitter, reproducible under virtual time.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Posted message waiting for its deadline. Timeouts of pending requests carry
+/// the id of the request, which is discarded once the timeout fires.
+struct Timer<T: Tag, M: Message> {
+    seq: u64,
+    tag: T,
+    msg: M,
+    ask: Option<u64>,
+    repeat: Option<Repeat>,
+}
+
+impl<T: Tag, M: Message> Timer<T, M> {
+    fn new(tag: T, msg: M) -> Self {
+        Self {
+            seq: 0,
+            tag,
+            msg,
+            ask: None,
+            repeat: None,
         }
     }
 }
 
-fn handle_posts<T: Tag, A: Actor<T = T, M = M>, M: Message>(
-    sys: &mut System<T, A, M>,
-    ctx: &mut Context<T, A, M>,
-) {
-    if sys.posted.is_empty() {
-        return;
+/// Deadline of a timer, the sequence number keeps posts with equal deadlines
+/// in FIFO order. An entry whose sequence number no longer matches its timer
+/// was cancelled or rescheduled, and is skipped.
+#[derive(PartialEq, Eq)]
+struct Post(Millis, u64, TimerId);
+
+impl PartialOrd for Post {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
     }
+}
 
-    while sys
-        .posted
-        .peek()
-        .map(|Post(deadline, _, _)| deadline)
-        .cloned()
-        .unwrap_or(Millis::MAX)
-        <= sys.millis
-    {
-        if let Some(Post(_, tag, msg)) = sys.posted.pop() {
-            if let Some(actor) = sys.actors.get_mut(&tag) {
-                actor.act(&tag, ctx, msg);
+/// Reversed, so that the max-heap `BinaryHeap` yields the earliest post first.
+impl Ord for Post {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (other.0, other.1).cmp(&(self.0, self.1))
+    }
+}
+
+/// How a system stops running once asked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shutdown {
+    /// Stop right away, leaving queued messages undelivered.
+    Now,
+    /// Keep processing until every mailbox is empty, including messages sent
+    /// while draining. Posts that are not due by then are left undelivered.
+    Drain,
+}
+
+/// Stops a running system from any thread.
+#[derive(Clone)]
+pub struct ShutdownHandle(Arc<dyn Fn(Shutdown) + Send + Sync>);
+
+impl ShutdownHandle {
+    /// Stops the system once the current iteration of its loop completes.
+    pub fn shutdown(&self) {
+        (self.0)(Shutdown::Now)
+    }
+
+    /// Stops the system once every mailbox has been processed.
+    pub fn drain(&self) {
+        (self.0)(Shutdown::Drain)
+    }
+}
+
+impl Debug for ShutdownHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ShutdownHandle")
+    }
+}
+
+/// Error returned by a `SystemHandle` once its system has finished running,
+/// see `System::finish`, or has been dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Closed;
+
+impl std::fmt::Display for Closed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("actor system is closed")
+    }
+}
+
+impl std::error::Error for Closed {}
+
+/// Gates of the blocking mailboxes of a system, shared with its handles.
+pub(crate) type Gates<T> = Arc<Mutex<HashMap<T, Arc<Gate>>>>;
+
+/// Injects messages into a system from any thread, see `System::handle`.
+pub struct SystemHandle<T: Tag, A: Actor, M: Message> {
+    tx: Sender<Action<T, A, M>>,
+    signal: Arc<Signal>,
+    gates: Gates<T>,
+    closed: Arc<AtomicBool>,
+}
+
+impl<T: Tag, A: Actor, M: Message> SystemHandle<T, A, M> {
+    fn act(&self, action: Action<T, A, M>) -> Result<(), Closed> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err(Closed);
+        }
+        self.tx.send(action).map_err(|_| Closed)?;
+        self.signal.notify();
+        Ok(())
+    }
+
+    /// Sends the message to the tag. If its mailbox blocks when full, waits
+    /// for room unless called from within the system, as `ActorRef::send`.
+    pub fn send(&self, tag: &T, msg: M) -> Result<(), Closed> {
+        let gate = self.gates.lock().unwrap().get(tag).cloned();
+        match gate {
+            Some(gate) if !Inside::is_set() && gate.acquire() => {
+                self.act(Action::Reserved(tag.clone(), msg))
             }
+            _ => self.act(Action::Send(tag.clone(), msg, None)),
         }
     }
+
+    /// See `Context::send_urgent`.
+    pub fn send_urgent(&self, tag: &T, msg: M) -> Result<(), Closed> {
+        self.act(Action::Urgent(tag.clone(), msg))
+    }
+
+    pub fn post(&self, tag: &T, msg: M, millis: Millis) -> Result<TimerId, Closed> {
+        let id = TimerId(next_id());
+        self.act(Action::Post(id, tag.clone(), msg, millis))?;
+        Ok(id)
+    }
+
+    pub fn schedule(&self, tag: &T, msg: M, schedule: Schedule) -> Result<TimerId, Closed> {
+        let id = TimerId(next_id());
+        self.act(Action::Schedule(id, tag.clone(), msg, schedule))?;
+        Ok(id)
+    }
+
+    pub fn cancel(&self, id: TimerId) -> Result<(), Closed> {
+        self.act(Action::Cancel(id))
+    }
+
+    pub fn reschedule(&self, id: TimerId, millis: Millis) -> Result<(), Closed> {
+        self.act(Action::Reschedule(id, millis))
+    }
+
+    pub fn stop(&self, tag: &T) -> Result<(), Closed> {
+        self.act(Action::Stop(tag.clone()))
+    }
+
+    /// Asks the system to stop running, see `Shutdown`.
+    pub fn shutdown(&self, mode: Shutdown) -> Result<(), Closed> {
+        self.act(Action::Shutdown(mode))
+    }
 }
 
-fn handle_actors<T: Tag, A: Actor<T = T, M = M>, M: Message>(
-    sys: &mut System<T, A, M>,
-    ctx: &mut Context<T, A, M>,
-) {
-    sys.queues
-        .iter_mut()
-        .filter(|(_, queue)| !queue.is_empty())
-        .map(|(tag, queue)| (tag, queue.pop_front()))
-        .for_each(|(tag, msg)| {
-            if let Some(msg) = msg {
-                if let Some(actor) = sys.actors.get_mut(tag) {
-                    actor.act(tag, ctx, msg);
-                }
-            }
-        });
+impl<T: Tag, A: Actor, M: Message> Clone for SystemHandle<T, A, M> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            signal: self.signal.clone(),
+            gates: self.gates.clone(),
+            closed: self.closed.clone(),
+        }
+    }
 }
 
-fn action_loop<T: Tag, A: Actor<T = T, M = M>, M: Message, F: Fn() -> Millis>(
-    sys: &mut System<T, A, M>,
-    clock: F,
-) {
-    let mut ctx = sys.context();
-    loop {
-        sys.millis = clock();
-        ctx.now = sys.millis;
+impl<T: Tag, A: Actor, M: Message> Debug for SystemHandle<T, A, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SystemHandle")
+    }
+}
 
-        handle_actions(sys);
-        handle_posts(sys, &mut ctx);
-        handle_actors(sys, &mut ctx);
+/// What happens when the handler of an actor returns an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// The error is printed to stderr and the actor keeps running.
+    #[default]
+    Log,
+    /// The actor is stopped.
+    Stop,
+    /// The actor is treated as if it panicked: it is restarted or stopped as
+    /// decided by the supervision of its parent.
+    Restart,
+    /// The actor is stopped and its parent is treated as if it panicked. An
+    /// error escalated past a root actor is fatal.
+    Escalate,
+    /// The error is fatal: the system shuts down right away.
+    Shutdown,
+}
+
+/// Fatal error returned by an actor, see `ErrorPolicy`.
+#[derive(Debug)]
+pub struct Failure<T: Tag> {
+    pub tag: T,
+    pub error: Error,
+}
+
+/// Messages left undelivered when a system stopped running.
+#[derive(Debug)]
+pub struct Outcome<T: Tag, M: Message> {
+    /// Time at which the run finished.
+    pub millis: Millis,
+    /// Messages still waiting in the mailboxes or stashed by their actors.
+    pub queued: Vec<(T, M)>,
+    /// Posted messages that were not due yet.
+    pub posted: Vec<(T, M)>,
+    /// The first fatal error, which shut the system down.
+    pub error: Option<Failure<T>>,
+}
+
+impl<T: Tag, M: Message> Outcome<T, M> {
+    /// Returns `true` if every message was delivered.
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty() && self.posted.is_empty()
+    }
+}
+
+/// Summary of a call to `System::tick`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tick {
+    /// Messages processed, or handed to worker threads.
+    pub processed: usize,
+    /// Deadline of the posted message that is due to fire next.
+    pub next_deadline: Option<Millis>,
+    /// No mailbox has work left: the host may sleep until `next_deadline`,
+    /// or until it sends a message. Actors still running on worker threads
+    /// may produce more work in the meantime.
+    pub idle: bool,
+    /// All actors have stopped or the system was shut down.
+    pub done: bool,
+}
+
+/// Creates instances of an actor, used to restart it after a failure.
+pub struct Factory<A: Actor>(Arc<dyn Fn() -> A + Send + Sync>);
+
+impl<A: Actor> Factory<A> {
+    pub fn new<F: Fn() -> A + Send + Sync + 'static>(f: F) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn create(&self) -> A {
+        (self.0)()
+    }
+}
+
+impl<A: Actor> Clone for Factory<A> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<A: Actor> Debug for Factory<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Factory")
+    }
+}
+
+/// Builds the message telling a watcher that the actor it watched is gone.
+pub struct Notify<T: Tag, M: Message>(Arc<dyn Fn(Terminated<T>) -> M + Send + Sync>);
+
+impl<T: Tag, M: Message> Notify<T, M> {
+    pub fn new<F: Fn(Terminated<T>) -> M + Send + Sync + 'static>(f: F) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn convert(&self, terminated: Terminated<T>) -> M {
+        (self.0)(terminated)
+    }
+}
+
+impl<T: Tag, M: Message> Clone for Notify<T, M> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Tag, M: Message> Debug for Notify<T, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Notify")
+    }
+}
+
+type Watchers<T, M> = Vec<(T, Notify<T, M>)>;
+
+/// Why a watched actor is gone, see `Context::watch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// The actor was stopped, directly or along with its parent.
+    Stopped,
+    /// The actor panicked, or returned an error handled as a failure. It may
+    /// have been restarted in its place.
+    Failed,
+    /// Another actor was bound to the tag, or the actor was restarted along
+    /// with a failed sibling.
+    Replaced,
+    /// No actor was bound to the tag when the watch began.
+    Unbound,
+}
+
+/// Tells a watcher that the actor bound to the tag is gone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Terminated<T: Tag> {
+    pub tag: T,
+    pub exit: Exit,
+}
+
+/// Which children are restarted when one of them fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Only the failed child is restarted.
+    #[default]
+    OneForOne,
+    /// All children of the parent are restarted.
+    OneForAll,
+    /// The failed child and the children bound after it are restarted.
+    RestForOne,
+}
+
+/// How a parent handles failures of its children. A child failing more than
+/// `max_restarts` times within `within` millis is stopped instead, and the
+/// failure is escalated to the parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Supervision {
+    pub strategy: Strategy,
+    pub max_restarts: usize,
+    pub within: Millis,
+}
+
+impl Supervision {
+    pub fn new(strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            ..Default::default()
+        }
+    }
+
+    pub fn limit(self, max_restarts: usize, within: Millis) -> Self {
+        Self {
+            max_restarts,
+            within,
+            ..self
+        }
+    }
+}
+
+impl Default for Supervision {
+    fn default() -> Self {
+        Self {
+            strategy: Strategy::OneForOne,
+            max_restarts: 3,
+            within: 5000,
+        }
+    }
+}
+
+/// Why a message could not be delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// No actor is bound to the tag the message was sent to.
+    Unbound,
+    /// The actor was stopped while the message was still in its mailbox.
+    Stopped,
+    /// A posted message fell due when no actor was bound to its tag.
+    Timer,
+    /// The mailbox of the tag was full.
+    Full,
+    /// The stash of the actor was full.
+    Stash,
+    /// The message was of a type the actor bound to the tag does not handle.
+    Mistyped,
+}
+
+/// Message that could not be delivered to its tag.
+#[derive(Debug, Clone)]
+pub struct DeadLetter<T: Tag, M: Message> {
+    pub tag: T,
+    pub msg: M,
+    pub reason: Reason,
+}
+
+enum DeadLetters<T: Tag, M: Message> {
+    Drop,
+    Callback(Box<dyn FnMut(DeadLetter<T, M>) + Send>),
+    Forward(T, fn(DeadLetter<T, M>) -> M),
+}
+
+/// Selects where actors are run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Executor {
+    /// Every actor runs on the thread that drives the system.
+    #[default]
+    Current,
+    /// Actors with pending messages are distributed across the given number of
+    /// worker threads. An actor is never run concurrently with itself.
+    Pool(usize),
+}
+
+/// How the loop shares its time between actors with pending messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheduling {
+    /// Actors take turns in the order they received their first pending
+    /// message, each processing up to the given number of messages per turn.
+    RoundRobin(usize),
+    /// Like `RoundRobin`, but each actor processes all messages that are in
+    /// its mailbox when its turn comes.
+    Exhaustive,
+}
+
+impl Scheduling {
+    fn throughput(&self) -> usize {
+        match self {
+            Self::RoundRobin(throughput) => (*throughput).max(1),
+            Self::Exhaustive => usize::MAX,
+        }
+    }
+}
+
+impl Default for Scheduling {
+    fn default() -> Self {
+        Self::RoundRobin(1)
+    }
+}
+
+pub struct System<T: Tag, A: Actor, M: Message> {
+    actors: HashMap<T, A>,
+    alive: HashMap<T, Arc<AtomicBool>>,
+    queues: HashMap<T, Queue<M>>,
+    stashes: HashMap<T, Vec<M>>,
+    behaviours: HashMap<T, Vec<Behaviour<T, A, M>>>,
+    mailboxes: HashMap<T, Mailbox<T, M>>,
+    /// Tags with pending messages, in the order of their turns. A tag leaves
+    /// once its turn comes, its mailbox is dropped as soon as it is empty.
+    /// Tags of stopped actors may linger until their turn comes.
+    ready: VecDeque<T>,
+    ready_set: HashSet<T>,
+    scheduling: Scheduling,
+    posted: BinaryHeap<Post>,
+    posts: u64,
+    timers: HashMap<TimerId, Timer<T, M>>,
+    asks: HashMap<u64, (T, TimerId)>,
+    running: HashSet<T>,
+    stopping: HashSet<T>,
+    /// Running tags whose stashes come back once they return from the worker,
+    /// ahead of the messages they did not get to.
+    unstashing: HashSet<T>,
+    parents: HashMap<T, T>,
+    children: HashMap<T, Vec<T>>,
+    factories: HashMap<T, Factory<A>>,
+    supervision: HashMap<T, Supervision>,
+    restarts: HashMap<T, Vec<Millis>>,
+    /// Watchers of each tag, along with the tags each watcher watches.
+    watchers: HashMap<T, Watchers<T, M>>,
+    watching: HashMap<T, HashSet<T>>,
+    shutdown: Option<Shutdown>,
+    error_policy: ErrorPolicy,
+    error: Option<Failure<T>>,
+    dead_letters: DeadLetters<T, M>,
+    dead_letters_count: usize,
+    pool: Option<Pool<T, A, M>>,
+    millis: Millis,
+    tx: Sender<Action<T, A, M>>,
+    rx: Receiver<Action<T, A, M>>,
+    signal: Arc<Signal>,
+    gates: Gates<T>,
+    /// Set once the system has finished, handles refuse further actions.
+    closed: Arc<AtomicBool>,
+}
+
+impl<T: Tag, A: Actor<T = T, M = M>, M: Message> Default for System<T, A, M> {
+    fn default() -> Self {
+        Self::new(Executor::Current)
+    }
+}
+
+impl<T: Tag, A: Actor<T = T, M = M>, M: Message> System<T, A, M> {
+    pub fn new(executor: Executor) -> Self {
+        let (tx, rx) = channel();
+        let signal = Arc::new(Signal::default());
+        let gates = Gates::default();
+        let pool = match executor {
+            Executor::Current => None,
+            Executor::Pool(threads) => Some(Pool::new(
+                threads.max(1),
+                Sink::channel(tx.clone(), signal.clone()),
+                gates.clone(),
+            )),
+        };
+        Self {
+            actors: Default::default(),
+            alive: Default::default(),
+            queues: Default::default(),
+            stashes: Default::default(),
+            behaviours: Default::default(),
+            mailboxes: Default::default(),
+            ready: Default::default(),
+            ready_set: Default::default(),
+            scheduling: Default::default(),
+            posted: Default::default(),
+            posts: 0,
+            timers: Default::default(),
+            asks: Default::default(),
+            running: Default::default(),
+            stopping: Default::default(),
+            unstashing: Default::default(),
+            parents: Default::default(),
+            children: Default::default(),
+            factories: Default::default(),
+            supervision: Default::default(),
+            restarts: Default::default(),
+            watchers: Default::default(),
+            watching: Default::default(),
+            shutdown: None,
+            error_policy: Default::default(),
+            error: None,
+            dead_letters: DeadLetters::Drop,
+            dead_letters_count: 0,
+            pool,
+            millis: 0,
+            tx,
+            rx,
+            signal,
+            gates,
+            closed: Default::default(),
+        }
+    }
+
+    pub fn context(&self) -> Context<T, A, M> {
+        Context::new(
+            Sink::channel(self.tx.clone(), self.signal.clone()),
+            self.gates.clone(),
+        )
+    }
+
+    pub fn bind(&mut self, tag: T, actor: A) -> ActorRef<T, M> {
+        self.context().bind(tag, actor)
+    }
+
+    pub fn bind_with(&mut self, tag: T, actor: A, mailbox: Mailbox<T, M>) -> ActorRef<T, M> {
+        self.context().bind_with(tag, actor, mailbox)
+    }
+
+    /// Returns a handle for sending messages to the system from other threads.
+    pub fn handle(&self) -> SystemHandle<T, A, M> {
+        SystemHandle {
+            tx: self.tx.clone(),
+            signal: self.signal.clone(),
+            gates: self.gates.clone(),
+            closed: self.closed.clone(),
+        }
+    }
+
+    /// Returns a handle that stops the system from another thread.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        let tx = self.tx.clone();
+        let signal = self.signal.clone();
+        ShutdownHandle(Arc::new(move |mode| {
+            if tx.send(Action::Shutdown(mode)).is_ok() {
+                signal.notify();
+            }
+        }))
+    }
+
+    pub fn set_scheduling(&mut self, scheduling: Scheduling) {
+        self.scheduling = scheduling;
+    }
+
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.error_policy = policy;
+    }
+
+    /// Passes every undeliverable message to `f`.
+    pub fn on_dead_letter<F: FnMut(DeadLetter<T, M>) + Send + 'static>(&mut self, f: F) {
+        self.dead_letters = DeadLetters::Callback(Box::new(f));
+    }
+
+    /// Delivers every undeliverable message to the actor bound to `tag`, as
+    /// converted by `into`. Dead letters of that actor itself are dropped.
+    pub fn forward_dead_letters(&mut self, tag: T, into: fn(DeadLetter<T, M>) -> M) {
+        self.dead_letters = DeadLetters::Forward(tag, into);
+    }
+
+    /// Returns the number of messages that could not be delivered so far.
+    pub fn dead_letters(&self) -> usize {
+        self.dead_letters_count
+    }
+
+    /// Returns the number of messages waiting in the mailbox of the tag.
+    pub fn queued(&self, tag: &T) -> usize {
+        self.queues.get(tag).map_or(0, Queue::len)
+    }
+
+    /// Returns the number of messages stashed by the actor bound to the tag.
+    pub fn stashed(&self, tag: &T) -> usize {
+        self.stashes.get(tag).map_or(0, Vec::len)
+    }
+
+    /// Returns the number of messages dropped or rejected by the bounded
+    /// mailbox of the tag since it was configured.
+    pub fn dropped(&self, tag: &T) -> usize {
+        self.mailboxes.get(tag).map_or(0, |mailbox| mailbox.dropped)
+    }
+
+    /// Runs the system on wall-clock time until all actors stop or it is shut
+    /// down. When no mailbox has work, the loop blocks until either a new action
+    /// arrives or the next posted deadline is reached instead of spinning.
+    pub fn run(&mut self) -> Outcome<T, M> {
+        loop {
+            self.step(get_current_millis());
+            if self.is_done() {
+                break;
+            }
+            if self.is_idle() {
+                self.wait(get_current_millis());
+            }
+        }
+        self.finish()
+    }
+
+    /// Runs the system reading the current time from `clock` on every iteration.
+    /// The clock is opaque to the loop, so it never sleeps between iterations.
+    pub fn run_with_clock<F: FnMut() -> Millis>(&mut self, clock: F) -> Outcome<T, M> {
+        action_loop(self, clock);
+        self.finish()
+    }
+
+    /// Runs the system on simulated time starting at `start`. Whenever no mailbox
+    /// has work left, the clock jumps straight to the next posted deadline, so
+    /// timer-heavy actors complete instantly and reproducibly. The run finishes
+    /// once all actors stop, the system is shut down, or nothing is left that
+    /// could ever make progress; in the latter case the actors remain bound.
+    pub fn run_virtual(&mut self, start: Millis) -> Outcome<T, M> {
+        let _inside = Inside::enter();
+        let mut now = start;
+        loop {
+            self.step(now);
+            if self.is_done() {
+                break;
+            }
+            if self.is_idle() {
+                if !self.running.is_empty() {
+                    if let Ok(action) = self.rx.recv() {
+                        handle_action(self, action);
+                    }
+                    continue;
+                }
+                match self.next_deadline() {
+                    Some(deadline) => now = now.max(deadline),
+                    None => {
+                        return Outcome {
+                            millis: now,
+                            queued: vec![],
+                            posted: vec![],
+                            error: None,
+                        }
+                    }
+                }
+            }
+        }
+        self.finish()
+    }
+
+    /// Performs a single iteration of the loop at time `now`: applies pending
+    /// actions, delivers due posts and gives every actor with pending messages
+    /// one turn, see `Scheduling`.
+    pub fn step(&mut self, now: Millis) {
+        let _inside = Inside::enter();
+        self.millis = now;
+
+        handle_actions(self);
+        handle_posts(self);
+        handle_actors(self, usize::MAX);
+        handle_actions(self);
+    }
+
+    /// Drives the system from a host loop at time `now`: applies pending
+    /// actions and delivers due posts, then lets actors take turns until either
+    /// `budget` messages are processed or no mailbox has work left. Messages
+    /// sent while ticking are processed within the same tick, budget allowing.
+    ///
+    /// The returned `Tick` tells the host how long it may sleep. Once it reports
+    /// `done`, call `finish` to stop the remaining actors.
+    pub fn tick(&mut self, now: Millis, budget: usize) -> Tick {
+        let _inside = Inside::enter();
+        self.millis = now;
+
+        handle_actions(self);
+        handle_posts(self);
+        let mut processed = 0;
+        while processed < budget && !self.is_idle() && !self.is_done() {
+            let turns = handle_actors(self, budget - processed);
+            handle_actions(self);
+            if turns == 0 {
+                break;
+            }
+            processed += turns;
+        }
+        Tick {
+            processed,
+            next_deadline: self.next_deadline(),
+            idle: self.is_idle(),
+            done: self.is_done(),
+        }
+    }
+
+    /// Same as `tick`, at the current wall-clock time.
+    pub fn poll(&mut self, budget: usize) -> Tick {
+        self.tick(get_current_millis(), budget)
+    }
+
+    /// Returns `true` if no mailbox holds a message that can be processed right
+    /// now. Messages for an actor that is busy on a worker thread do not count.
+    pub fn is_idle(&self) -> bool {
+        self.ready.iter().all(|tag| self.running.contains(tag))
+    }
+
+    /// Returns a context for running the actor bound to the tag on the thread
+    /// driving the system.
+    fn local_context(&self, tag: &T) -> Context<T, A, M> {
+        let mut ctx = self.context();
+        ctx.now = self.millis;
+        ctx.tag = Some(tag.clone());
+        ctx
+    }
+
+    /// Returns `true` if an actor is bound to the tag and not being stopped.
+    fn is_bound(&self, tag: &T) -> bool {
+        self.actors.contains_key(tag)
+            || (self.running.contains(tag) && !self.stopping.contains(tag))
+    }
+
+    /// Returns `true` once the loop should exit: there are no bound actors
+    /// left, including those running on worker threads, or the system has been
+    /// shut down and, when draining, all mailboxes are empty.
+    fn is_done(&self) -> bool {
+        match self.shutdown {
+            Some(Shutdown::Now) => true,
+            Some(Shutdown::Drain) => self.running.is_empty() && self.queues.is_empty(),
+            None => self.actors.is_empty() && self.running.is_empty(),
+        }
+    }
+
+    /// Waits for actors running on worker threads, collects what was left
+    /// undelivered and stops all remaining actors. Called by the `run` methods
+    /// on exit; hosts driving the system with `tick` call it themselves. Its
+    /// handles return `Closed` from then on.
+    pub fn finish(&mut self) -> Outcome<T, M> {
+        let _inside = Inside::enter();
+        self.closed.store(true, Ordering::Release);
+        while !self.running.is_empty() {
+            if let Ok(action) = self.rx.recv() {
+                handle_action(self, action);
+            }
+        }
+        handle_actions(self);
+        let mut queued = take_queued(self);
+
+        // Every actor is about to stop, there is nobody left to tell.
+        self.watchers.clear();
+        self.watching.clear();
+        let roots: Vec<T> = self
+            .actors
+            .keys()
+            .filter(|tag| !self.parents.contains_key(tag))
+            .cloned()
+            .collect();
+        for tag in roots {
+            stop_actor(self, tag);
+        }
+        handle_actions(self);
+        queued.extend(take_queued(self));
+
+        let posted = std::mem::take(&mut self.posted)
+            .into_sorted_vec()
+            .into_iter()
+            .rev()
+            .filter_map(|post| take_timer(self, post))
+            .map(|timer| (timer.tag, timer.msg))
+            .collect();
+        self.timers.clear();
+        self.asks.clear();
+        self.shutdown = None;
+        Outcome {
+            millis: self.millis,
+            queued,
+            posted,
+            error: self.error.take(),
+        }
+    }
+
+    /// Returns the deadline of the posted message that is due to fire next.
+    /// Cancelled and rescheduled entries never stay on top of the heap.
+    pub fn next_deadline(&self) -> Option<Millis> {
+        self.posted.peek().map(|Post(deadline, ..)| *deadline)
+    }
+
+    /// Blocks until an action arrives or the next posted deadline is reached.
+    fn wait(&mut self, now: Millis) {
+        let action = match self.next_deadline() {
+            Some(deadline) if deadline <= now => return,
+            Some(deadline) => self
+                .rx
+                .recv_timeout(Duration::from_millis(deadline - now))
+                .ok(),
+            None => self.rx.recv().ok(),
+        };
+        if let Some(action) = action {
+            handle_action(self, action);
+        }
+    }
+}
+
+/// Marks the current thread as running the system or an actor for as long as
+/// the guard lives, so that sending to a full mailbox never blocks it.
+struct Inside;
+
+thread_local! {
+    static INSIDE: Cell<usize> = const { Cell::new(0) };
+}
+
+impl Inside {
+    fn enter() -> Self {
+        INSIDE.with(|inside| inside.set(inside.get() + 1));
+        Self
+    }
+
+    fn is_set() -> bool {
+        INSIDE.with(|inside| inside.get() > 0)
+    }
+}
+
+impl Drop for Inside {
+    fn drop(&mut self) {
+        INSIDE.with(|inside| inside.set(inside.get() - 1));
+    }
+}
+
+fn get_current_millis() -> Millis {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as Millis
+}
+
+fn take_queued<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+) -> Vec<(T, M)> {
+    for (tag, queue) in &sys.queues {
+        release(sys, tag, queue.len());
+    }
+    let mut queued: Vec<(T, M)> = sys
+        .queues
+        .drain()
+        .flat_map(|(tag, queue)| queue.into_iter().map(move |msg| (tag.clone(), msg)))
+        .collect();
+    queued.extend(
+        sys.stashes
+            .drain()
+            .flat_map(|(tag, stash)| stash.into_iter().map(move |msg| (tag.clone(), msg))),
+    );
+    queued
+}
+
+/// Queues the message ahead of all others in the mailbox of the tag.
+fn deliver_urgent<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    tag: T,
+    msg: M,
+) {
+    if sys.is_bound(&tag) {
+        if let Some(gate) = sys
+            .mailboxes
+            .get(&tag)
+            .and_then(|mailbox| mailbox.gate.as_ref())
+        {
+            gate.force(1);
+        }
+        make_ready(sys, &tag);
+        sys.queues.entry(tag).or_default().push_urgent(msg);
+    } else {
+        dead_letter(sys, tag, msg, Reason::Unbound);
+    }
+}
+
+fn deliver<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    tag: T,
+    msg: M,
+    from: Option<T>,
+) {
+    if sys.is_bound(&tag) {
+        enqueue(sys, tag, msg, from);
+    } else {
+        dead_letter(sys, tag, msg, Reason::Unbound);
+    }
+}
+
+/// Appends the message to the mailbox of the tag, applying the overflow
+/// policy if the mailbox is bounded and full.
+fn enqueue<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    tag: T,
+    msg: M,
+    from: Option<T>,
+) {
+    make_ready(sys, &tag);
+    let queue = sys.queues.entry(tag.clone()).or_default();
+    let mailbox = match sys.mailboxes.get_mut(&tag) {
+        Some(mailbox) => mailbox,
+        None => {
+            queue.push(msg, 0);
+            return;
+        }
+    };
+    let priority = mailbox
+        .priority
+        .as_ref()
+        .map_or(0, |priority| priority(&msg));
+    let room = match (&mailbox.gate, mailbox.capacity) {
+        (Some(gate), _) => gate.try_acquire(),
+        (None, Some(capacity)) => queue.len() < capacity,
+        (None, None) => true,
+    };
+    if room {
+        queue.push(msg, priority);
+        return;
+    }
+    mailbox.dropped += 1;
+    match mailbox.overflow.clone() {
+        Overflow::DropOldest => {
+            // Urgent messages are never dropped, if they fill the mailbox the
+            // incoming message goes instead.
+            match queue.pop_last() {
+                Some(oldest) => {
+                    queue.push(msg, priority);
+                    dead_letter(sys, tag, oldest, Reason::Full);
+                }
+                None => dead_letter(sys, tag, msg, Reason::Full),
+            }
+        }
+        Overflow::Reject(f) => match from {
+            Some(from) if from != tag && sys.is_bound(&from) => {
+                let letter = DeadLetter {
+                    tag,
+                    msg,
+                    reason: Reason::Full,
+                };
+                enqueue(sys, from, f(letter), None);
+            }
+            _ => dead_letter(sys, tag, msg, Reason::Full),
+        },
+        Overflow::DropNewest | Overflow::Block => dead_letter(sys, tag, msg, Reason::Full),
+    }
+}
+
+/// Gives the tag a turn after the tags that already have one, unless it has.
+fn make_ready<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>, tag: &T) {
+    if sys.ready_set.insert(tag.clone()) {
+        sys.ready.push_back(tag.clone());
+    }
+}
+
+/// Gives back room taken by messages that left the mailbox of the tag.
+fn release<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &System<T, A, M>,
+    tag: &T,
+    count: usize,
+) {
+    if let Some(gate) = sys
+        .mailboxes
+        .get(tag)
+        .and_then(|mailbox| mailbox.gate.as_ref())
+    {
+        gate.release(count);
+    }
+}
+
+fn dead_letter<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    tag: T,
+    msg: M,
+    reason: Reason,
+) {
+    sys.dead_letters_count += 1;
+    let letter = DeadLetter { tag, msg, reason };
+    match &mut sys.dead_letters {
+        DeadLetters::Drop => (),
+        DeadLetters::Callback(f) => f(letter),
+        DeadLetters::Forward(target, into) => {
+            let (target, into) = (target.clone(), *into);
+            if letter.tag != target && sys.is_bound(&target) {
+                enqueue(sys, target, into(letter), None);
+            }
+        }
+    }
+}
+
+fn handle_actions<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>) {
+    while let Ok(action) = sys.rx.try_recv() {
+        handle_action(sys, action);
+    }
+}
+
+fn handle_action<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    action: Action<T, A, M>,
+) {
+    match action {
+        Action::Bind(tag, mut actor, alive) => {
+            if let Some(replaced) = sys.alive.insert(tag.clone(), alive) {
+                replaced.store(false, Ordering::Release);
+            }
+            sys.factories.remove(&tag);
+            if sys.is_bound(&tag) {
+                end_watches(sys, &tag, Exit::Replaced);
+                unstash(sys, &tag);
+            }
+            let mut ctx = sys.local_context(&tag);
+            let restart = if let Some(mut replaced) = sys.actors.remove(&tag) {
+                replaced.on_stop(&tag, &mut ctx);
+                true
+            } else if sys.running.contains(&tag) && !sys.stopping.contains(&tag) {
+                // The replaced actor is stopped once it returns from the worker.
+                sys.stopping.insert(tag.clone());
+                true
+            } else {
+                false
+            };
+            if restart {
+                actor.on_restart(&tag, &mut ctx);
+            } else {
+                actor.on_start(&tag, &mut ctx);
+            }
+            keep_behaviours(sys, &tag, &mut ctx);
+            sys.actors.insert(tag, actor);
+        }
+        Action::Send(tag, msg, from) => {
+            deliver(sys, tag, msg, from);
+        }
+        Action::Urgent(tag, msg) => {
+            deliver_urgent(sys, tag, msg);
+        }
+        Action::Reserved(tag, msg) => {
+            if sys.is_bound(&tag) {
+                let priority = match sys.mailboxes.get(&tag) {
+                    Some(Mailbox {
+                        priority: Some(priority),
+                        ..
+                    }) => priority(&msg),
+                    _ => 0,
+                };
+                make_ready(sys, &tag);
+                sys.queues.entry(tag).or_default().push(msg, priority);
+            } else {
+                dead_letter(sys, tag, msg, Reason::Unbound);
+            }
+        }
+        Action::Mailbox(tag, mailbox) => {
+            if sys.is_bound(&tag) {
+                let mut gates = sys.gates.lock().unwrap();
+                match &mailbox.gate {
+                    Some(gate) => {
+                        gate.reset(sys.queues.get(&tag).map_or(0, Queue::len));
+                        gates.insert(tag.clone(), gate.clone());
+                    }
+                    None => {
+                        gates.remove(&tag);
+                    }
+                }
+                drop(gates);
+                if let Some(replaced) = sys.mailboxes.insert(tag, mailbox) {
+                    replaced.gate.inspect(|gate| gate.close());
+                }
+            } else {
+                mailbox.gate.inspect(|gate| gate.close());
+            }
+        }
+        Action::Stash(tag, msg) => {
+            if !sys.is_bound(&tag) {
+                dead_letter(sys, tag, msg, Reason::Unbound);
+                return;
+            }
+            let capacity = sys
+                .mailboxes
+                .get(&tag)
+                .map_or(STASH_CAPACITY, |mailbox| mailbox.stash);
+            let stash = sys.stashes.entry(tag.clone()).or_default();
+            if stash.len() < capacity {
+                stash.push(msg);
+            } else {
+                dead_letter(sys, tag, msg, Reason::Stash);
+            }
+        }
+        Action::Unstash(tag) => {
+            if sys.running.contains(&tag) {
+                sys.unstashing.insert(tag);
+            } else {
+                unstash(sys, &tag);
+            }
+        }
+        Action::Post(id, tag, msg, millis) => {
+            push_timer(sys, id, sys.millis + millis, Timer::new(tag, msg));
+        }
+        Action::Schedule(id, tag, msg, schedule) => {
+            if schedule.times != Some(0) {
+                let repeat = Repeat {
+                    schedule,
+                    origin: sys.millis.saturating_add(schedule.delay),
+                    index: 0,
+                    fired: 0,
+                    seed: sys.posts,
+                };
+                if let Some(deadline) = repeat.deadline() {
+                    let timer = Timer {
+                        repeat: Some(repeat),
+                        ..Timer::new(tag, msg)
+                    };
+                    push_timer(sys, id, deadline, timer);
+                }
+            }
+        }
+        Action::Cancel(id) => {
+            sys.timers.remove(&id);
+            purge_posts(sys);
+        }
+        Action::Reschedule(id, millis) => {
+            if let Some(mut timer) = sys.timers.remove(&id) {
+                let deadline = sys.millis + millis;
+                if let Some(repeat) = &mut timer.repeat {
+                    repeat.origin = deadline;
+                    repeat.index = 0;
+                }
+                push_timer(sys, id, deadline, timer);
+                purge_posts(sys);
+            }
+        }
+        Action::Ask(id, tag, expired, millis) => {
+            let timer = TimerId(next_id());
+            sys.asks.insert(id, (tag.clone(), timer));
+            let deadline = sys.millis + millis;
+            let expired = Timer {
+                ask: Some(id),
+                ..Timer::new(tag, expired)
+            };
+            push_timer(sys, timer, deadline, expired);
+        }
+        Action::Reply(id, msg) => {
+            if let Some((tag, timer)) = sys.asks.remove(&id) {
+                sys.timers.remove(&timer);
+                purge_posts(sys);
+                deliver(sys, tag, msg, None);
+            }
+        }
+        Action::Link(tag, parent, factory) => {
+            unlink(sys, &tag);
+            if let Some(parent) = parent.filter(|parent| sys.is_bound(parent)) {
+                sys.children
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(tag.clone());
+                sys.parents.insert(tag.clone(), parent);
+            }
+            if let Some(factory) = factory {
+                sys.factories.insert(tag, factory);
+            }
+        }
+        Action::Supervise(tag, supervision) => {
+            if sys.is_bound(&tag) {
+                sys.supervision.insert(tag, supervision);
+            }
+        }
+        Action::Watch(watcher, tag, notify) => {
+            if !sys.is_bound(&watcher) {
+                return;
+            }
+            if !sys.is_bound(&tag) {
+                let msg = notify.convert(Terminated {
+                    tag,
+                    exit: Exit::Unbound,
+                });
+                deliver_urgent(sys, watcher, msg);
+                return;
+            }
+            let watchers = sys.watchers.entry(tag.clone()).or_default();
+            watchers.retain(|(other, _)| other != &watcher);
+            watchers.push((watcher.clone(), notify));
+            sys.watching.entry(watcher).or_default().insert(tag);
+        }
+        Action::Unwatch(watcher, tag) => {
+            unwatch(sys, &watcher, &tag);
+        }
+        Action::Stop(tag) => {
+            stop_actor(sys, tag);
+        }
+        Action::Dead(tag, msg, reason) => {
+            dead_letter(sys, tag, msg, reason);
+        }
+        Action::Shutdown(mode) => {
+            if sys.shutdown.is_none() || mode == Shutdown::Now {
+                sys.shutdown = Some(mode);
+            }
+        }
+        Action::Error(tag, error) => {
+            handle_error(sys, tag, error);
+        }
+        Action::Done(tag, actor, msgs, behaviours) => {
+            sys.running.remove(&tag);
+            let stopping = sys.stopping.remove(&tag);
+            let unstashing = sys.unstashing.remove(&tag);
+            match actor {
+                Some(mut actor) if stopping => {
+                    actor.on_stop(&tag, &mut sys.local_context(&tag));
+                }
+                Some(actor) => {
+                    sys.actors.insert(tag.clone(), actor);
+                    if !behaviours.is_empty() {
+                        sys.behaviours.insert(tag.clone(), behaviours);
+                    }
+                }
+                None if stopping => (),
+                None => handle_failure(sys, tag.clone()),
+            }
+            if sys.is_bound(&tag) {
+                if let Some(gate) = sys
+                    .mailboxes
+                    .get(&tag)
+                    .and_then(|mailbox| mailbox.gate.as_ref())
+                {
+                    gate.force(msgs.len());
+                }
+                if !msgs.is_empty() {
+                    sys.queues.entry(tag.clone()).or_default().requeue(msgs);
+                }
+                if unstashing {
+                    unstash(sys, &tag);
+                }
+                if sys.queues.contains_key(&tag) {
+                    make_ready(sys, &tag);
+                }
+            } else {
+                for msg in msgs {
+                    dead_letter(sys, tag.clone(), msg, Reason::Stopped);
+                }
+            }
+        }
+    }
+}
+
+fn unlink<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>, tag: &T) {
+    if let Some(parent) = sys.parents.remove(tag) {
+        if let Some(children) = sys.children.get_mut(&parent) {
+            children.retain(|child| child != tag);
+        }
+    }
+}
+
+/// Stops the actor bound to the tag together with all its descendants.
+fn stop_actor<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>, tag: T) {
+    for child in sys.children.remove(&tag).unwrap_or_default() {
+        stop_actor(sys, child);
+    }
+    unlink(sys, &tag);
+    end_watches(sys, &tag, Exit::Stopped);
+    sys.factories.remove(&tag);
+    sys.supervision.remove(&tag);
+    sys.restarts.remove(&tag);
+    sys.behaviours.remove(&tag);
+    if let Some(alive) = sys.alive.remove(&tag) {
+        alive.store(false, Ordering::Release);
+    }
+    if let Some(mut actor) = sys.actors.remove(&tag) {
+        actor.on_stop(&tag, &mut sys.local_context(&tag));
+    } else if sys.running.contains(&tag) {
+        sys.stopping.insert(tag.clone());
+    }
+    if let Some(gate) = sys.mailboxes.remove(&tag).and_then(|mailbox| mailbox.gate) {
+        sys.gates.lock().unwrap().remove(&tag);
+        gate.close();
+    }
+    let queue = sys.queues.remove(&tag).unwrap_or_default();
+    for msg in queue
+        .into_iter()
+        .chain(sys.stashes.remove(&tag).unwrap_or_default())
+    {
+        dead_letter(sys, tag.clone(), msg, Reason::Stopped);
+    }
+}
+
+/// Keeps the behaviours pushed through the context of the tag for its next
+/// messages.
+fn keep_behaviours<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    tag: &T,
+    ctx: &mut Context<T, A, M>,
+) {
+    let behaviours = std::mem::take(&mut ctx.behaviours);
+    if behaviours.is_empty() {
+        sys.behaviours.remove(tag);
+    } else {
+        sys.behaviours.insert(tag.clone(), behaviours);
+    }
+}
+
+/// Puts the stashed messages of the tag back ahead of its mailbox.
+fn unstash<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>, tag: &T) {
+    let stash = match sys.stashes.remove(tag) {
+        Some(stash) => stash,
+        None => return,
+    };
+    if let Some(gate) = sys
+        .mailboxes
+        .get(tag)
+        .and_then(|mailbox| mailbox.gate.as_ref())
+    {
+        gate.force(stash.len());
+    }
+    sys.queues.entry(tag.clone()).or_default().requeue(stash);
+    make_ready(sys, tag);
+}
+
+/// Tells the watchers of the tag that its actor is gone, and ends the watches
+/// of that actor on others.
+fn end_watches<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    tag: &T,
+    exit: Exit,
+) {
+    for watched in sys.watching.remove(tag).unwrap_or_default() {
+        if let Some(watchers) = sys.watchers.get_mut(&watched) {
+            watchers.retain(|(watcher, _)| watcher != tag);
+            if watchers.is_empty() {
+                sys.watchers.remove(&watched);
+            }
+        }
+    }
+    for (watcher, notify) in sys.watchers.remove(tag).unwrap_or_default() {
+        if let Some(watched) = sys.watching.get_mut(&watcher) {
+            watched.remove(tag);
+            if watched.is_empty() {
+                sys.watching.remove(&watcher);
+            }
+        }
+        let terminated = Terminated {
+            tag: tag.clone(),
+            exit,
+        };
+        deliver_urgent(sys, watcher, notify.convert(terminated));
+    }
+}
+
+fn unwatch<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    watcher: &T,
+    tag: &T,
+) {
+    if let Some(watched) = sys.watching.get_mut(watcher) {
+        watched.remove(tag);
+        if watched.is_empty() {
+            sys.watching.remove(watcher);
+        }
+    }
+    if let Some(watchers) = sys.watchers.get_mut(tag) {
+        watchers.retain(|(other, _)| other != watcher);
+        if watchers.is_empty() {
+            sys.watchers.remove(tag);
+        }
+    }
+}
+
+/// Replaces the actor bound to the tag with a fresh one from its factory,
+/// stopping its children. Actors without a factory are left as they are.
+fn restart_actor<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>, tag: T) {
+    let factory = match sys.factories.get(&tag) {
+        Some(factory) => factory.clone(),
+        None => return,
+    };
+    for child in sys.children.remove(&tag).unwrap_or_default() {
+        stop_actor(sys, child);
+    }
+    end_watches(sys, &tag, Exit::Replaced);
+    unstash(sys, &tag);
+    let mut ctx = sys.local_context(&tag);
+    if let Some(mut actor) = sys.actors.remove(&tag) {
+        actor.on_stop(&tag, &mut ctx);
+    } else if sys.running.contains(&tag) {
+        sys.stopping.insert(tag.clone());
+    }
+    let mut actor = factory.create();
+    actor.on_restart(&tag, &mut ctx);
+    keep_behaviours(sys, &tag, &mut ctx);
+    sys.actors.insert(tag, actor);
+}
+
+/// Applies the supervision of the parent after the actor bound to the tag
+/// failed. A panicked instance is expected to be discarded by the caller, as
+/// it is not fit for `on_stop`; an escalating parent is stopped normally.
+fn handle_failure<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>, tag: T) {
+    if !sys.alive.contains_key(&tag) {
+        return;
+    }
+    end_watches(sys, &tag, Exit::Failed);
+    if !sys.factories.contains_key(&tag) {
+        stop_actor(sys, tag);
+        return;
+    }
+
+    let parent = sys.parents.get(&tag).cloned();
+    let supervision = parent
+        .as_ref()
+        .and_then(|parent| sys.supervision.get(parent))
+        .copied()
+        .unwrap_or_default();
+    let now = sys.millis;
+    let restarts = sys.restarts.entry(tag.clone()).or_default();
+    restarts.retain(|at| at + supervision.within > now);
+    if restarts.len() >= supervision.max_restarts {
+        stop_actor(sys, tag);
+        if let Some(parent) = parent {
+            handle_failure(sys, parent);
+        }
+        return;
+    }
+    restarts.push(now);
+
+    let siblings = parent
+        .as_ref()
+        .and_then(|parent| sys.children.get(parent))
+        .cloned()
+        .unwrap_or_default();
+    let affected = match supervision.strategy {
+        _ if parent.is_none() => vec![tag],
+        Strategy::OneForOne => vec![tag],
+        Strategy::OneForAll => siblings,
+        Strategy::RestForOne => siblings
+            .into_iter()
+            .skip_while(|sibling| sibling != &tag)
+            .collect(),
+    };
+    for tag in affected {
+        restart_actor(sys, tag);
+    }
+}
+
+/// Applies the error policy after the handler of the actor bound to the tag
+/// returned an error.
+fn handle_error<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    tag: T,
+    error: Error,
+) {
+    if !sys.alive.contains_key(&tag) {
+        return;
+    }
+    match sys.error_policy {
+        ErrorPolicy::Log => eprintln!("[tag={:?}] error: {}", tag, error),
+        ErrorPolicy::Stop => stop_actor(sys, tag),
+        ErrorPolicy::Restart => handle_failure(sys, tag),
+        ErrorPolicy::Escalate => match sys.parents.get(&tag).cloned() {
+            Some(parent) => {
+                stop_actor(sys, tag);
+                handle_failure(sys, parent);
+            }
+            None => {
+                stop_actor(sys, tag.clone());
+                fatal(sys, tag, error);
+            }
+        },
+        ErrorPolicy::Shutdown => fatal(sys, tag, error),
+    }
+}
+
+/// Shuts the system down right away, keeping the first fatal error.
+fn fatal<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    tag: T,
+    error: Error,
+) {
+    if sys.error.is_none() {
+        sys.error = Some(Failure { tag, error });
+    }
+    sys.shutdown = Some(Shutdown::Now);
+}
+
+/// Why an actor did not handle a message.
+pub(crate) enum Fault {
+    Error(Error),
+    Panic,
+}
+
+/// Runs the actor on a single message.
+fn invoke<A: Actor>(
+    actor: &mut A,
+    tag: &A::T,
+    ctx: &mut Context<A::T, A, A::M>,
+    msg: A::M,
+) -> Result<(), Fault> {
+    ctx.tag = Some(tag.clone());
+    let _inside = Inside::enter();
+    match catch_unwind(AssertUnwindSafe(|| dispatch(actor, tag, ctx, msg))) {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(error)) => Err(Fault::Error(error)),
+        Err(_) => Err(Fault::Panic),
+    }
+}
+
+/// Hands the message to the behaviour on top of the stack of the context, or
+/// to `try_act` if there is none.
+fn dispatch<A: Actor>(
+    actor: &mut A,
+    tag: &A::T,
+    ctx: &mut Context<A::T, A, A::M>,
+    msg: A::M,
+) -> Result<(), Error> {
+    match ctx.behaviours.last().copied() {
+        Some(behaviour) => behaviour(actor, tag, ctx, msg),
+        None => actor.try_act(tag, ctx, msg),
+    }
+}
+
+fn push_timer<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    id: TimerId,
+    deadline: Millis,
+    mut timer: Timer<T, M>,
+) {
+    sys.posts += 1;
+    timer.seq = sys.posts;
+    sys.timers.insert(id, timer);
+    sys.posted.push(Post(deadline, sys.posts, id));
+}
+
+/// Removes the timer of the heap entry, unless the entry is stale.
+fn take_timer<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    Post(_, seq, id): Post,
+) -> Option<Timer<T, M>> {
+    match sys.timers.get(&id) {
+        Some(timer) if timer.seq == seq => sys.timers.remove(&id),
+        _ => None,
+    }
+}
+
+/// Drops stale entries from the top of the heap, or from the whole heap once
+/// they outnumber the live ones, as when a timeout is reset on every message.
+fn purge_posts<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>) {
+    let timers = &sys.timers;
+    if sys.posted.len() > 2 * timers.len() {
+        sys.posted
+            .retain(|Post(_, seq, id)| timers.get(id).is_some_and(|timer| timer.seq == *seq));
+        return;
+    }
+    while let Some(Post(_, seq, id)) = sys.posted.peek() {
+        if sys.timers.get(id).is_some_and(|timer| timer.seq == *seq) {
+            break;
+        }
+        sys.posted.pop();
+    }
+}
+
+fn handle_posts<T: Tag, A: Actor<T = T, M = M>, M: Message>(sys: &mut System<T, A, M>) {
+    while sys
+        .posted
+        .peek()
+        .is_some_and(|Post(deadline, ..)| *deadline <= sys.millis)
+    {
+        let post = match sys.posted.pop() {
+            Some(post) => post,
+            None => break,
+        };
+        let id = post.2;
+        let Timer {
+            tag,
+            msg,
+            ask,
+            repeat,
+            ..
+        } = match take_timer(sys, post) {
+            Some(timer) => timer,
+            None => continue,
+        };
+        if let Some(ask) = ask {
+            sys.asks.remove(&ask);
+        }
+        if !sys.is_bound(&tag) {
+            dead_letter(sys, tag, msg, Reason::Timer);
+            continue;
+        }
+        if let Some(mut repeat) = repeat {
+            repeat.fired += 1;
+            repeat.index += 1;
+            let left = repeat
+                .schedule
+                .times
+                .is_none_or(|times| repeat.fired < times);
+            if let Some(deadline) = repeat.deadline().filter(|_| left) {
+                let timer = Timer {
+                    repeat: Some(repeat),
+                    ..Timer::new(tag.clone(), msg.clone())
+                };
+                push_timer(sys, id, deadline, timer);
+            }
+        }
+        enqueue(sys, tag, msg, None);
+    }
+    purge_posts(sys);
+}
+
+/// Gives a turn to every ready actor, until `budget` messages are processed.
+/// Returns the number of messages processed or handed to worker threads.
+fn handle_actors<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    sys: &mut System<T, A, M>,
+    budget: usize,
+) -> usize {
+    // Messages left once the system is shut down right away stay queued for
+    // the outcome.
+    if sys.shutdown == Some(Shutdown::Now) {
+        return 0;
+    }
+    let mut ctx = sys.context();
+    ctx.now = sys.millis;
+    let mut processed = 0;
+    let mut failed = Vec::new();
+    // Only tags that were ready before this step get a turn, those readied
+    // during the turns wait for the next step.
+    for _ in 0..sys.ready.len() {
+        if processed >= budget {
+            break;
+        }
+        let throughput = sys.scheduling.throughput().min(budget - processed);
+        let tag = match sys.ready.pop_front() {
+            Some(tag) => tag,
+            None => break,
+        };
+        sys.ready_set.remove(&tag);
+        // A running actor is readied again once it returns from the worker.
+        if sys.running.contains(&tag) {
+            continue;
+        }
+        let (queue, actor) = match (sys.queues.get_mut(&tag), sys.actors.get_mut(&tag)) {
+            (Some(queue), Some(actor)) => (queue, actor),
+            _ => continue,
+        };
+        let gate = sys
+            .mailboxes
+            .get(&tag)
+            .and_then(|mailbox| mailbox.gate.as_ref());
+        match &sys.pool {
+            Some(pool) => {
+                let msgs: Vec<M> = std::iter::from_fn(|| queue.pop())
+                    .take(throughput)
+                    .collect();
+                if let Some(gate) = gate {
+                    gate.release(msgs.len());
+                }
+                processed += msgs.len();
+                if queue.is_empty() {
+                    sys.queues.remove(&tag);
+                }
+                if let Some(actor) = sys.actors.remove(&tag) {
+                    sys.running.insert(tag.clone());
+                    pool.submit(Job {
+                        behaviours: sys.behaviours.remove(&tag).unwrap_or_default(),
+                        tag,
+                        actor,
+                        msgs,
+                        now: sys.millis,
+                    });
+                }
+            }
+            None => {
+                ctx.behaviours = sys.behaviours.remove(&tag).unwrap_or_default();
+                ctx.unstashed = false;
+                for _ in 0..throughput {
+                    let msg = match queue.pop() {
+                        Some(msg) => msg,
+                        None => break,
+                    };
+                    if let Some(gate) = gate {
+                        gate.release(1);
+                    }
+                    processed += 1;
+                    if let Err(fault) = invoke(actor, &tag, &mut ctx, msg) {
+                        failed.push((tag.clone(), fault));
+                        break;
+                    }
+                    if ctx.unstashed {
+                        break;
+                    }
+                }
+                if queue.is_empty() {
+                    sys.queues.remove(&tag);
+                } else if failed.last().map(|(failed, _)| failed) != Some(&tag) {
+                    make_ready(sys, &tag);
+                }
+                keep_behaviours(sys, &tag, &mut ctx);
+            }
+        }
+    }
+    for (tag, fault) in failed {
+        match fault {
+            Fault::Error(error) => handle_error(sys, tag.clone(), error),
+            Fault::Panic => {
+                sys.actors.remove(&tag);
+                handle_failure(sys, tag.clone());
+            }
+        }
+        if sys.is_bound(&tag) && sys.queues.contains_key(&tag) {
+            make_ready(sys, &tag);
+        }
+    }
+    processed
+}
+
+fn action_loop<T: Tag, A: Actor<T = T, M = M>, M: Message, F: FnMut() -> Millis>(
+    sys: &mut System<T, A, M>,
+    mut clock: F,
+) {
+    loop {
+        sys.step(clock());
 
-        if sys.actors.is_empty() {
+        if sys.is_done() {
             break;
         }
     }
@@ -0,0 +1,312 @@
+use crate::{DeadLetter, Message, Tag};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Debug,
+    sync::{Arc, Condvar, Mutex},
+};
+
+/// What happens to a message sent to a mailbox that is full.
+pub enum Overflow<T: Tag, M: Message> {
+    /// The incoming message is dropped.
+    DropNewest,
+    /// The oldest message in the mailbox is dropped to make room.
+    DropOldest,
+    /// The incoming message is converted by the function and delivered back to
+    /// the actor that sent it. Messages sent from outside the system are
+    /// dropped.
+    Reject(Arc<dyn Fn(DeadLetter<T, M>) -> M + Send + Sync>),
+    /// Senders outside the system wait through their `ActorRef` until there is
+    /// room. Actors are never blocked, their messages are dropped instead.
+    Block,
+}
+
+impl<T: Tag, M: Message> Overflow<T, M> {
+    pub fn reject<F: Fn(DeadLetter<T, M>) -> M + Send + Sync + 'static>(f: F) -> Self {
+        Self::Reject(Arc::new(f))
+    }
+}
+
+impl<T: Tag, M: Message> Clone for Overflow<T, M> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::DropNewest => Self::DropNewest,
+            Self::DropOldest => Self::DropOldest,
+            Self::Reject(f) => Self::Reject(f.clone()),
+            Self::Block => Self::Block,
+        }
+    }
+}
+
+impl<T: Tag, M: Message> Debug for Overflow<T, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DropNewest => f.write_str("DropNewest"),
+            Self::DropOldest => f.write_str("DropOldest"),
+            Self::Reject(_) => f.write_str("Reject"),
+            Self::Block => f.write_str("Block"),
+        }
+    }
+}
+
+/// Messages an actor may stash unless its mailbox is configured otherwise.
+pub(crate) const STASH_CAPACITY: usize = 1024;
+
+/// Orders the messages of a mailbox: higher priorities are processed first,
+/// messages of equal priority in the order they were sent.
+pub type Priority<M> = Arc<dyn Fn(&M) -> u8 + Send + Sync>;
+
+/// Configuration of the mailbox of an actor, see `Context::bind_with`.
+///
+/// A bounded mailbox holds at most `capacity` messages. Messages that do not
+/// fit are handled by the overflow policy, dropped ones become dead letters.
+/// Urgent messages are always accepted and processed before any other.
+///
+/// The stash of the actor holds at most `stash_capacity` messages, see
+/// `Context::stash`. It defaults to 1024.
+#[derive(Clone)]
+pub struct Mailbox<T: Tag, M: Message> {
+    pub(crate) capacity: Option<usize>,
+    pub(crate) overflow: Overflow<T, M>,
+    pub(crate) priority: Option<Priority<M>>,
+    pub(crate) stash: usize,
+    pub(crate) gate: Option<Arc<Gate>>,
+    pub(crate) dropped: usize,
+}
+
+impl<T: Tag, M: Message> Mailbox<T, M> {
+    pub fn unbounded() -> Self {
+        Self {
+            capacity: None,
+            overflow: Overflow::DropNewest,
+            priority: None,
+            stash: STASH_CAPACITY,
+            gate: None,
+            dropped: 0,
+        }
+    }
+
+    pub fn bounded(capacity: usize, overflow: Overflow<T, M>) -> Self {
+        Self {
+            capacity: Some(capacity.max(1)),
+            overflow,
+            ..Self::unbounded()
+        }
+    }
+
+    /// Orders the messages by `priority`, see `Priority`.
+    pub fn priority<F: Fn(&M) -> u8 + Send + Sync + 'static>(self, priority: F) -> Self {
+        Self {
+            priority: Some(Arc::new(priority)),
+            ..self
+        }
+    }
+
+    pub fn stash(self, capacity: usize) -> Self {
+        Self {
+            stash: capacity,
+            ..self
+        }
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    pub fn overflow(&self) -> &Overflow<T, M> {
+        &self.overflow
+    }
+
+    pub fn stash_capacity(&self) -> usize {
+        self.stash
+    }
+}
+
+impl<T: Tag, M: Message> Debug for Mailbox<T, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mailbox")
+            .field("capacity", &self.capacity)
+            .field("overflow", &self.overflow)
+            .field("priority", &self.priority.is_some())
+            .field("stash", &self.stash)
+            .finish()
+    }
+}
+
+/// Messages waiting for an actor. Urgent messages come first, then the lanes
+/// of non-zero priority from the highest, then messages of priority zero.
+pub(crate) struct Queue<M> {
+    urgent: VecDeque<M>,
+    lanes: BTreeMap<u8, VecDeque<M>>,
+    normal: VecDeque<M>,
+    len: usize,
+}
+
+impl<M> Default for Queue<M> {
+    fn default() -> Self {
+        Self {
+            urgent: VecDeque::new(),
+            lanes: BTreeMap::new(),
+            normal: VecDeque::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<M> Queue<M> {
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(crate) fn push_urgent(&mut self, msg: M) {
+        self.urgent.push_back(msg);
+        self.len += 1;
+    }
+
+    pub(crate) fn push(&mut self, msg: M, priority: u8) {
+        match priority {
+            0 => self.normal.push_back(msg),
+            _ => self.lanes.entry(priority).or_default().push_back(msg),
+        }
+        self.len += 1;
+    }
+
+    /// Puts back messages that were taken but not processed, so that they are
+    /// processed next.
+    pub(crate) fn requeue(&mut self, msgs: Vec<M>) {
+        self.len += msgs.len();
+        for msg in msgs.into_iter().rev() {
+            self.urgent.push_front(msg);
+        }
+    }
+
+    /// Takes the message to be processed next.
+    pub(crate) fn pop(&mut self) -> Option<M> {
+        let msg = match self.urgent.pop_front() {
+            Some(msg) => Some(msg),
+            None => match self.lanes.last_entry() {
+                Some(mut lane) => {
+                    let msg = lane.get_mut().pop_front();
+                    if lane.get().is_empty() {
+                        lane.remove();
+                    }
+                    msg
+                }
+                None => self.normal.pop_front(),
+            },
+        };
+        self.len -= msg.is_some() as usize;
+        msg
+    }
+
+    /// Takes the oldest message of the lowest priority, urgent ones are kept.
+    pub(crate) fn pop_last(&mut self) -> Option<M> {
+        let msg = match self.normal.pop_front() {
+            Some(msg) => Some(msg),
+            None => match self.lanes.first_entry() {
+                Some(mut lane) => {
+                    let msg = lane.get_mut().pop_front();
+                    if lane.get().is_empty() {
+                        lane.remove();
+                    }
+                    msg
+                }
+                None => None,
+            },
+        };
+        self.len -= msg.is_some() as usize;
+        msg
+    }
+}
+
+impl<M> IntoIterator for Queue<M> {
+    type Item = M;
+    type IntoIter = std::vec::IntoIter<M>;
+
+    /// Yields the messages in the order they would have been processed.
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut msgs = Vec::with_capacity(self.len);
+        msgs.extend(self.urgent.drain(..));
+        while let Some((_, lane)) = self.lanes.pop_last() {
+            msgs.extend(lane);
+        }
+        msgs.extend(self.normal);
+        msgs.into_iter()
+    }
+}
+
+/// Room left in a blocking mailbox, shared with the `ActorRef`s of the actor.
+/// Counts the queued messages together with those still on their way, for
+/// which a blocked sender has already taken room.
+#[derive(Debug)]
+pub(crate) struct Gate {
+    capacity: usize,
+    state: Mutex<GateState>,
+    room: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct GateState {
+    used: usize,
+    closed: bool,
+}
+
+impl Gate {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Default::default(),
+            room: Condvar::new(),
+        }
+    }
+
+    /// Waits until there is room and takes it, returns `false` if the mailbox
+    /// was closed in the meantime.
+    pub(crate) fn acquire(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        while state.used >= self.capacity && !state.closed {
+            state = self.room.wait(state).unwrap();
+        }
+        if state.closed {
+            return false;
+        }
+        state.used += 1;
+        true
+    }
+
+    /// Takes room without waiting, returns `false` if there is none.
+    pub(crate) fn try_acquire(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.used >= self.capacity || state.closed {
+            return false;
+        }
+        state.used += 1;
+        true
+    }
+
+    /// Takes room even if there is none, for messages that bypass the bounds.
+    pub(crate) fn force(&self, count: usize) {
+        self.state.lock().unwrap().used += count;
+    }
+
+    pub(crate) fn release(&self, count: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.used = state.used.saturating_sub(count);
+        self.room.notify_all();
+    }
+
+    pub(crate) fn reset(&self, used: usize) {
+        self.state.lock().unwrap().used = used;
+        self.room.notify_all();
+    }
+
+    /// Wakes up all waiting senders. Their messages are then sent as if the
+    /// mailbox did not block, so the overflow policy in place applies.
+    pub(crate) fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.room.notify_all();
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{invoke, Action, Actor, Behaviour, Context, Fault, Gates, Message, Millis, Sink, Tag};
+use std::{
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// A batch of messages to be processed in order by an actor on a worker thread.
+pub(crate) struct Job<T: Tag, A: Actor, M: Message> {
+    pub(crate) tag: T,
+    pub(crate) actor: A,
+    pub(crate) msgs: Vec<M>,
+    pub(crate) now: Millis,
+    pub(crate) behaviours: Vec<Behaviour<T, A, M>>,
+}
+
+/// Fixed set of worker threads pulling jobs from a shared queue, so an idle
+/// worker always picks up the next ready actor. Each actor is handed back to
+/// the system through `Action::Done` once its job is complete.
+pub(crate) struct Pool<T: Tag, A: Actor, M: Message> {
+    jobs: Option<Sender<Job<T, A, M>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<T: Tag, A: Actor<T = T, M = M>, M: Message> Pool<T, A, M> {
+    pub(crate) fn new(threads: usize, tx: Sink<T, A, M>, gates: Gates<T>) -> Self {
+        let (jobs, rx) = channel();
+        let rx = Arc::new(Mutex::new(rx));
+        let workers = (0..threads)
+            .map(|_| {
+                let rx = rx.clone();
+                let tx = tx.clone();
+                let gates = gates.clone();
+                thread::spawn(move || worker(rx, tx, gates))
+            })
+            .collect();
+        Self {
+            jobs: Some(jobs),
+            workers,
+        }
+    }
+}
+
+impl<T: Tag, A: Actor, M: Message> Pool<T, A, M> {
+    pub(crate) fn submit(&self, job: Job<T, A, M>) {
+        if let Some(jobs) = &self.jobs {
+            jobs.send(job).unwrap();
+        }
+    }
+}
+
+impl<T: Tag, A: Actor, M: Message> Drop for Pool<T, A, M> {
+    fn drop(&mut self) {
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn worker<T: Tag, A: Actor<T = T, M = M>, M: Message>(
+    rx: Arc<Mutex<Receiver<Job<T, A, M>>>>,
+    tx: Sink<T, A, M>,
+    gates: Gates<T>,
+) {
+    let mut ctx = Context::new(tx.clone(), gates);
+    loop {
+        let job = rx.lock().unwrap().recv();
+        let Job {
+            tag,
+            mut actor,
+            msgs,
+            now,
+            behaviours,
+        } = match job {
+            Ok(job) => job,
+            Err(_) => break,
+        };
+        ctx.now = now;
+        ctx.behaviours = behaviours;
+        ctx.unstashed = false;
+        let mut msgs = msgs.into_iter();
+        let mut fault = None;
+        for msg in &mut msgs {
+            fault = invoke(&mut actor, &tag, &mut ctx, msg).err();
+            // The messages not handled yet go back behind the unstashed ones.
+            if fault.is_some() || ctx.unstashed {
+                break;
+            }
+        }
+        let behaviours = std::mem::take(&mut ctx.behaviours);
+        match fault {
+            None => tx.send(Action::Done(tag, Some(actor), msgs.collect(), behaviours)),
+            Some(Fault::Error(error)) => {
+                // The policy applies while the actor is still running, so it
+                // is not handed its next message before.
+                tx.send(Action::Error(tag.clone(), error));
+                tx.send(Action::Done(tag, Some(actor), msgs.collect(), behaviours));
+            }
+            Some(Fault::Panic) => tx.send(Action::Done(tag, None, msgs.collect(), Vec::new())),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use doing_more_actors::{Actor, Context, Message, System};
+
+#[derive(Debug, Clone)]
+struct Noop;
+
+impl Message for Noop {}
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {
+    type T = String;
+    type M = Noop;
+
+    fn act(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Noop>, _msg: Noop) {}
+}
+
+#[test]
+fn reference_dies_with_rebind_and_stop() {
+    let tag = "idle".to_string();
+    let mut sys = System::default();
+    let first = sys.bind(tag.clone(), Idle);
+    sys.step(0);
+    assert!(first.is_alive());
+
+    let second = sys.bind(tag.clone(), Idle);
+    sys.step(0);
+    assert!(!first.is_alive());
+    assert!(second.is_alive());
+
+    sys.context().stop(&tag);
+    sys.step(0);
+    assert!(!second.is_alive());
+}
@@ -0,0 +1,79 @@
+use doing_more_actors::{Actor, Context, Message, Millis, ReplyTo, System};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+enum Msg {
+    /// Asks the server, which replies after the given delay, if any.
+    Ask(Option<Millis>),
+    Request(ReplyTo<Msg>, Option<Millis>),
+    Answer,
+    Value(u32),
+    Expired,
+}
+
+impl Message for Msg {}
+
+const TIMEOUT: Millis = 100;
+
+/// Client or server: records the replies and expirations it receives, with
+/// the time at which they arrive.
+#[derive(Debug)]
+struct Peer {
+    log: Arc<Mutex<Vec<(String, Millis)>>>,
+    pending: Option<ReplyTo<Msg>>,
+}
+
+impl Actor for Peer {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Ask(delay) => {
+                let server = "server".to_string();
+                let request = |reply| Msg::Request(reply, delay);
+                ctx.ask(tag, &server, request, TIMEOUT, Msg::Expired);
+            }
+            Msg::Request(reply, None) => reply.reply(Msg::Value(42)),
+            Msg::Request(reply, Some(delay)) => {
+                self.pending = Some(reply);
+                ctx.post(tag.clone(), Msg::Answer, delay);
+            }
+            Msg::Answer => {
+                if let Some(reply) = self.pending.take() {
+                    reply.reply(Msg::Value(7));
+                }
+            }
+            Msg::Value(n) => self.log.lock().unwrap().push((n.to_string(), ctx.now())),
+            Msg::Expired => self.log.lock().unwrap().push(("expired".into(), ctx.now())),
+        }
+    }
+}
+
+fn ask(delay: Option<Millis>) -> Vec<(String, Millis)> {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    for tag in ["client", "server"] {
+        let peer = Peer {
+            log: log.clone(),
+            pending: None,
+        };
+        sys.bind(tag.to_string(), peer);
+    }
+    sys.context().send(&"client".to_string(), Msg::Ask(delay));
+    let outcome = sys.run_virtual(0);
+    assert!(outcome.posted.is_empty());
+    let log = log.lock().unwrap();
+    log.clone()
+}
+
+#[test]
+fn reply_is_delivered_to_asker() {
+    assert_eq!(ask(None), vec![("42".to_string(), 0)]);
+    assert_eq!(ask(Some(50)), vec![("7".to_string(), 50)]);
+}
+
+#[test]
+fn expired_message_arrives_at_timeout_and_late_reply_is_dropped() {
+    assert_eq!(ask(Some(150)), vec![("expired".to_string(), TIMEOUT)]);
+}
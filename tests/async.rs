@@ -0,0 +1,147 @@
+#![cfg(feature = "async")]
+
+use doing_more_actors::{Actor, Context, Message, ReplyTo, Schedule, System};
+use std::{
+    future::Future,
+    pin::{pin, Pin},
+    sync::Arc,
+    task::{Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+/// Minimal executor parking the current thread until the future is woken.
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = std::task::Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+/// Sleep of a runtime, backed by a timer thread.
+struct Sleep {
+    until: Instant,
+    started: bool,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<()> {
+        let now = Instant::now();
+        if now >= self.until {
+            return Poll::Ready(());
+        }
+        if !self.started {
+            self.started = true;
+            let (waker, delay) = (cx.waker().clone(), self.until - now);
+            thread::spawn(move || {
+                thread::sleep(delay);
+                waker.wake();
+            });
+        }
+        Poll::Pending
+    }
+}
+
+fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        until: Instant::now() + duration,
+        started: false,
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Msg {
+    Get(ReplyTo<Msg>),
+    Ignore(ReplyTo<Msg>),
+    Tick,
+    Value(u32),
+}
+
+impl Message for Msg {}
+
+#[derive(Debug)]
+struct Counter {
+    count: u32,
+    stop_at: u32,
+}
+
+impl Actor for Counter {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Get(reply) => reply.reply(Msg::Value(self.count)),
+            Msg::Ignore(reply) => drop(reply),
+            Msg::Tick => {
+                self.count += 1;
+                if self.count == self.stop_at {
+                    ctx.stop(tag);
+                }
+            }
+            Msg::Value(_) => (),
+        }
+    }
+}
+
+#[test]
+fn external_code_awaits_replies() {
+    let mut sys = System::default();
+    let counter = sys.bind(
+        "counter".to_string(),
+        Counter {
+            count: 0,
+            stop_at: 0,
+        },
+    );
+    let shutdown = sys.shutdown_handle();
+    let runner = thread::spawn(move || block_on(sys.run_async(sleep)));
+
+    counter.send(Msg::Tick);
+    counter.send(Msg::Tick);
+    let (reply, response) = ReplyTo::channel();
+    counter.send(Msg::Get(reply));
+    assert!(matches!(block_on(response), Some(Msg::Value(2))));
+
+    let (reply, response) = ReplyTo::channel();
+    counter.send(Msg::Ignore(reply));
+    assert!(block_on(response).is_none());
+
+    shutdown.shutdown();
+    assert!(runner.join().unwrap().is_empty());
+}
+
+#[test]
+fn timers_sleep_on_the_runtime() {
+    let mut sys = System::default();
+    let mut ctx = sys.context();
+    let tag = "counter".to_string();
+    ctx.bind(
+        tag.clone(),
+        Counter {
+            count: 0,
+            stop_at: 3,
+        },
+    );
+    ctx.schedule(tag, Msg::Tick, Schedule::every(20));
+
+    let started = Instant::now();
+    let outcome = block_on(sys.run_async(sleep));
+    assert!(started.elapsed() >= Duration::from_millis(55));
+    assert!(outcome.queued.is_empty());
+}
@@ -0,0 +1,126 @@
+use doing_more_actors::{Actor, Context, DynSystem, Error, Executor, Message, System};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+enum Msg {
+    Coin,
+    Push,
+    Service,
+}
+
+impl Message for Msg {}
+
+type Log = Arc<Mutex<Vec<&'static str>>>;
+
+/// Locked until a coin is inserted, closed for everything during service.
+#[derive(Debug)]
+struct Turnstile {
+    log: Log,
+    unlocked: bool,
+}
+
+impl Turnstile {
+    fn record(&self, entry: &'static str) {
+        self.log.lock().unwrap().push(entry);
+    }
+}
+
+type Ctx = Context<String, Turnstile, Msg>;
+
+fn unlocked(turnstile: &mut Turnstile, _: &String, ctx: &mut Ctx, msg: Msg) -> Result<(), Error> {
+    match msg {
+        Msg::Coin => turnstile.record("thanks"),
+        Msg::Push => {
+            turnstile.record("pass");
+            ctx.pop_behaviour();
+        }
+        Msg::Service => ctx.push_behaviour(service),
+    }
+    Ok(())
+}
+
+fn service(turnstile: &mut Turnstile, _: &String, ctx: &mut Ctx, msg: Msg) -> Result<(), Error> {
+    match msg {
+        Msg::Service => {
+            ctx.pop_behaviour();
+        }
+        _ => turnstile.record("closed"),
+    }
+    Ok(())
+}
+
+impl Actor for Turnstile {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, _tag: &String, ctx: &mut Ctx, msg: Msg) {
+        match msg {
+            Msg::Coin => {
+                self.record("unlock");
+                ctx.push_behaviour(unlocked);
+            }
+            Msg::Push => self.record("locked"),
+            Msg::Service => ctx.push_behaviour(service),
+        }
+    }
+
+    fn on_start(&mut self, _tag: &String, ctx: &mut Ctx) {
+        if self.unlocked {
+            ctx.push_behaviour(unlocked);
+        }
+    }
+}
+
+const SCRIPT: [Msg; 9] = [
+    Msg::Coin,
+    Msg::Coin,
+    Msg::Push,
+    Msg::Push,
+    Msg::Service,
+    Msg::Coin,
+    Msg::Service,
+    Msg::Coin,
+    Msg::Push,
+];
+
+const EXPECTED: [&str; 7] = [
+    "unlock", "thanks", "pass", "locked", "closed", "unlock", "pass",
+];
+
+#[test]
+fn behaviours_take_messages_until_popped() {
+    for executor in [Executor::Current, Executor::Pool(2)] {
+        let log = Log::default();
+        let mut sys = System::new(executor);
+        let turnstile = sys.bind(
+            "turnstile".to_string(),
+            Turnstile {
+                log: log.clone(),
+                unlocked: false,
+            },
+        );
+        for msg in SCRIPT {
+            turnstile.send(msg);
+        }
+        assert!(sys.run_virtual(0).is_empty());
+        assert_eq!(*log.lock().unwrap(), EXPECTED);
+    }
+}
+
+#[test]
+fn hosted_actors_keep_their_behaviours() {
+    let log = Log::default();
+    let mut sys = DynSystem::default();
+    let turnstile = sys.context().spawn(
+        "turnstile".to_string(),
+        Turnstile {
+            log: log.clone(),
+            unlocked: true,
+        },
+    );
+    for msg in SCRIPT.into_iter().skip(1) {
+        turnstile.send(msg);
+    }
+    assert!(sys.run_virtual(0).is_empty());
+    assert_eq!(*log.lock().unwrap(), EXPECTED[1..]);
+}
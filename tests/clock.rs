@@ -0,0 +1,78 @@
+use doing_more_actors::{Actor, Context, Message, Millis, System};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+enum Msg {
+    /// Posts a tick after each of the given delays.
+    Start(Vec<Millis>),
+    Tick,
+}
+
+impl Message for Msg {}
+
+/// Records the time of every tick, and stops after the last one.
+#[derive(Debug)]
+struct Ticker {
+    left: usize,
+    ticks: Arc<Mutex<Vec<Millis>>>,
+}
+
+impl Actor for Ticker {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Start(delays) => {
+                self.left = delays.len();
+                for delay in delays {
+                    ctx.post(tag.clone(), Msg::Tick, delay);
+                }
+            }
+            Msg::Tick => {
+                self.ticks.lock().unwrap().push(ctx.now());
+                self.left -= 1;
+                if self.left == 0 {
+                    ctx.stop(tag);
+                }
+            }
+        }
+    }
+}
+
+fn ticker(sys: &mut System<String, Ticker, Msg>, delays: Vec<Millis>) -> Arc<Mutex<Vec<Millis>>> {
+    let ticks = Arc::new(Mutex::new(Vec::new()));
+    let ticker = Ticker {
+        left: 0,
+        ticks: ticks.clone(),
+    };
+    sys.bind("ticker".to_string(), ticker)
+        .send(Msg::Start(delays));
+    ticks
+}
+
+#[test]
+fn virtual_clock_jumps_to_the_next_deadline() {
+    let mut sys = System::default();
+    let ticks = ticker(&mut sys, vec![4_000, 1_000]);
+    let outcome = sys.run_virtual(500);
+    assert_eq!(*ticks.lock().unwrap(), vec![1_500, 4_500]);
+    assert_eq!(outcome.millis, 4_500);
+}
+
+#[test]
+fn run_with_clock_reads_the_given_clock() {
+    let mut sys = System::default();
+    let ticks = ticker(&mut sys, vec![25]);
+    let mut now = 0;
+    let mut readings = 0;
+    let outcome = sys.run_with_clock(|| {
+        readings += 1;
+        now += 10;
+        now
+    });
+    // Posted at the first reading, the tick is due at 35.
+    assert_eq!(*ticks.lock().unwrap(), vec![40]);
+    assert_eq!(outcome.millis, 40);
+    assert_eq!(readings, 4);
+}
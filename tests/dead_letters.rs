@@ -0,0 +1,98 @@
+use doing_more_actors::{Actor, Context, DeadLetter, Mailbox, Message, Reason, System};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Msg {
+    Item(u32),
+    Letter(String, u32, Reason),
+}
+
+impl Message for Msg {}
+
+fn letter(letter: DeadLetter<String, Msg>) -> Msg {
+    match letter.msg {
+        Msg::Item(n) => Msg::Letter(letter.tag, n, letter.reason),
+        msg => msg,
+    }
+}
+
+/// Records the letters it receives and stashes every item.
+#[derive(Debug)]
+struct Office {
+    letters: Arc<Mutex<Vec<Msg>>>,
+}
+
+impl Actor for Office {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, _tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Item(_) => ctx.stash(msg),
+            letter => self.letters.lock().unwrap().push(letter),
+        }
+    }
+}
+
+/// Sends item 1 to a tag that was never bound, items 2 and 3 to an actor
+/// stopped before it runs, and posts item 4 to it for later.
+fn undeliverable(sys: &mut System<String, Office, Msg>) {
+    let busy = "busy".to_string();
+    sys.bind(
+        busy.clone(),
+        Office {
+            letters: Default::default(),
+        },
+    );
+    let mut ctx = sys.context();
+    ctx.send(&"nobody".to_string(), Msg::Item(1));
+    ctx.send(&busy, Msg::Item(2));
+    ctx.send(&busy, Msg::Item(3));
+    ctx.post(busy.clone(), Msg::Item(4), 10);
+    ctx.stop(&busy);
+}
+
+fn expected() -> Vec<Msg> {
+    vec![
+        Msg::Letter("nobody".to_string(), 1, Reason::Unbound),
+        Msg::Letter("busy".to_string(), 2, Reason::Stopped),
+        Msg::Letter("busy".to_string(), 3, Reason::Stopped),
+        Msg::Letter("busy".to_string(), 4, Reason::Timer),
+    ]
+}
+
+#[test]
+fn undeliverable_messages_are_reported_with_their_reason() {
+    let letters = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let log = letters.clone();
+    sys.on_dead_letter(move |dead| log.lock().unwrap().push(letter(dead)));
+    // Keeps the system running until the post falls due.
+    let office = Office {
+        letters: Default::default(),
+    };
+    sys.bind("office".to_string(), office);
+    undeliverable(&mut sys);
+    sys.run_virtual(0);
+    assert_eq!(*letters.lock().unwrap(), expected());
+    assert_eq!(sys.dead_letters(), 4);
+}
+
+#[test]
+fn forwarded_dead_letters_skip_those_of_the_target() {
+    let letters = Arc::new(Mutex::new(Vec::new()));
+    let office = "office".to_string();
+    let mut sys = System::default();
+    let actor = Office {
+        letters: letters.clone(),
+    };
+    // Without room in its stash, every item sent to the office is a dead
+    // letter of its own.
+    sys.bind_with(office.clone(), actor, Mailbox::unbounded().stash(0))
+        .send(Msg::Item(5));
+    sys.forward_dead_letters(office, letter);
+    undeliverable(&mut sys);
+    sys.run_virtual(0);
+    assert_eq!(*letters.lock().unwrap(), expected());
+    assert_eq!(sys.dead_letters(), 5);
+}
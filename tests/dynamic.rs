@@ -0,0 +1,132 @@
+use doing_more_actors::{Actor, ActorRef, Context, DeadLetter, DynSystem, Message, Packet, Reason};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+struct Ping;
+
+impl Message for Ping {}
+
+#[derive(Debug, Clone)]
+struct Pong;
+
+impl Message for Pong {}
+
+/// Counts the pings it receives.
+#[derive(Debug)]
+struct Pinged(Arc<Mutex<u32>>);
+
+impl Actor for Pinged {
+    type T = String;
+    type M = Ping;
+
+    fn act(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Ping>, _msg: Ping) {
+        *self.0.lock().unwrap() += 1;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Double(u32, ActorRef<String, Doubled>);
+
+impl Message for Double {}
+
+#[derive(Debug, Clone)]
+struct Doubled(u32);
+
+impl Message for Doubled {}
+
+/// Replies with twice the number it is given.
+#[derive(Debug)]
+struct Doubler;
+
+impl Actor for Doubler {
+    type T = String;
+    type M = Double;
+
+    fn act(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Double>, msg: Double) {
+        let Double(n, reply) = msg;
+        reply.send(Doubled(n * 2));
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Collect {
+    Ask(u32, ActorRef<String, Doubled>),
+    Reply(u32),
+}
+
+impl Message for Collect {}
+
+/// Asks the doubler for each number it is given, and records the replies.
+#[derive(Debug)]
+struct Collector {
+    doubler: ActorRef<String, Double>,
+    replies: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Actor for Collector {
+    type T = String;
+    type M = Collect;
+
+    fn act(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Collect>, msg: Collect) {
+        match msg {
+            Collect::Ask(n, reply) => self.doubler.send(Double(n, reply)),
+            Collect::Reply(n) => self.replies.lock().unwrap().push(n),
+        }
+    }
+}
+
+/// Forwards the replies of the doubler to the collector, in its own type.
+#[derive(Debug)]
+struct Relay(ActorRef<String, Collect>);
+
+impl Actor for Relay {
+    type T = String;
+    type M = Doubled;
+
+    fn act(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Doubled>, msg: Doubled) {
+        self.0.send(Collect::Reply(msg.0));
+    }
+}
+
+#[test]
+fn actors_of_different_types_talk_through_typed_references() {
+    let replies = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = DynSystem::default();
+    let mut ctx = sys.context();
+    let doubler = ctx.spawn("doubler".to_string(), Doubler);
+    let collector = Collector {
+        doubler,
+        replies: replies.clone(),
+    };
+    let collector = ctx.spawn("collector".to_string(), collector);
+    let relay = ctx.spawn("relay".to_string(), Relay(collector.clone()));
+    for n in [1, 2, 3] {
+        collector.send(Collect::Ask(n, relay.clone()));
+    }
+    assert!(sys.run_virtual(0).is_empty());
+    assert_eq!(*replies.lock().unwrap(), vec![2, 4, 6]);
+    assert_eq!(sys.dead_letters(), 0);
+}
+
+#[test]
+fn mistyped_packets_become_dead_letters() {
+    let pings = Arc::new(Mutex::new(0));
+    let letters = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = DynSystem::default();
+    let log = letters.clone();
+    sys.on_dead_letter(move |letter: DeadLetter<String, Packet>| {
+        let pong = letter.msg.downcast::<Pong>().is_ok();
+        log.lock().unwrap().push((letter.tag, pong, letter.reason));
+    });
+    let tag = "pinged".to_string();
+    let mut ctx = sys.context();
+    ctx.spawn(tag.clone(), Pinged(pings.clone())).send(Ping);
+    ctx.send(&tag, Packet::new(Pong));
+    assert!(sys.run_virtual(0).is_empty());
+    assert_eq!(*pings.lock().unwrap(), 1);
+    assert_eq!(sys.dead_letters(), 1);
+    assert_eq!(
+        *letters.lock().unwrap(),
+        vec![(tag, true, Reason::Mistyped)]
+    );
+}
@@ -0,0 +1,141 @@
+use doing_more_actors::{Context, Error, ErrorPolicy, Executor, FallibleActor, Message, System};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+struct Work(u32);
+
+impl Message for Work {}
+
+/// Fails on odd work items, recording the ones it completed along with its
+/// generation, which counts restarts. The root worker supervises a child.
+#[derive(Debug)]
+struct Worker {
+    generation: u32,
+    log: Arc<Mutex<Vec<(u32, u32)>>>,
+}
+
+impl FallibleActor for Worker {
+    type T = String;
+    type M = Work;
+
+    fn try_act(
+        &mut self,
+        _tag: &String,
+        _ctx: &mut Context<String, Self, Work>,
+        Work(n): Work,
+    ) -> Result<(), Error> {
+        if n % 2 == 1 {
+            return Err(format!("odd item {n}").into());
+        }
+        self.log.lock().unwrap().push((self.generation, n));
+        Ok(())
+    }
+
+    fn on_start(&mut self, tag: &String, ctx: &mut Context<String, Self, Work>) {
+        if self.generation > 0 {
+            return;
+        }
+        let (log, generation) = (self.log.clone(), Arc::new(Mutex::new(0)));
+        ctx.supervise(format!("{tag}/child"), move || {
+            let mut generation = generation.lock().unwrap();
+            *generation += 1;
+            Worker {
+                generation: *generation,
+                log: log.clone(),
+            }
+        });
+    }
+}
+
+/// Work items for the child, the second of which fails.
+const ITEMS: [u32; 4] = [0, 1, 2, 4];
+
+/// Sends the given work items to the child of a root worker and to the root,
+/// under the given policy.
+fn run(
+    executor: Executor,
+    policy: ErrorPolicy,
+    child_items: &[u32],
+    root_items: &[u32],
+) -> (Vec<(u32, u32)>, Option<String>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::new(executor);
+    sys.set_error_policy(policy);
+    let root = sys.bind(
+        "root".to_string(),
+        Worker {
+            generation: 0,
+            log: log.clone(),
+        },
+    );
+    sys.step(0);
+    let handle = sys.handle();
+    for n in child_items {
+        handle.send(&"root/child".to_string(), Work(*n)).unwrap();
+    }
+    for n in root_items {
+        root.send(Work(*n));
+    }
+    let outcome = sys.run_virtual(0);
+    let error = outcome
+        .error
+        .map(|failure| format!("{}: {}", failure.tag, failure.error));
+    let log = log.lock().unwrap();
+    (log.clone(), error)
+}
+
+#[test]
+fn logged_errors_keep_actor_running() {
+    for executor in [Executor::Current, Executor::Pool(2)] {
+        let (log, error) = run(executor, ErrorPolicy::Log, &ITEMS, &[]);
+        assert_eq!(log, vec![(1, 0), (1, 2), (1, 4)]);
+        assert!(error.is_none());
+    }
+}
+
+#[test]
+fn stopped_actor_processes_nothing_more() {
+    for executor in [Executor::Current, Executor::Pool(2)] {
+        let (log, error) = run(executor, ErrorPolicy::Stop, &ITEMS, &[]);
+        assert_eq!(log, vec![(1, 0)]);
+        assert!(error.is_none());
+    }
+}
+
+#[test]
+fn restarted_actor_continues_with_next_message() {
+    for executor in [Executor::Current, Executor::Pool(2)] {
+        let (log, error) = run(executor, ErrorPolicy::Restart, &ITEMS, &[]);
+        assert_eq!(log, vec![(1, 0), (2, 2), (2, 4)]);
+        assert!(error.is_none());
+    }
+}
+
+#[test]
+fn escalated_error_stops_parent() {
+    for executor in [Executor::Current, Executor::Pool(2)] {
+        let (log, error) = run(executor, ErrorPolicy::Escalate, &ITEMS, &[]);
+        assert_eq!(log, vec![(1, 0)]);
+        assert!(error.is_none());
+    }
+}
+
+#[test]
+fn escalated_error_of_root_is_fatal() {
+    for executor in [Executor::Current, Executor::Pool(2)] {
+        // Left alone, the child would escalate to the root at the same time
+        // on the pool.
+        let (log, error) = run(executor, ErrorPolicy::Escalate, &[], &[3]);
+        assert!(log.is_empty());
+        assert_eq!(error.as_deref(), Some("root: odd item 3"));
+    }
+}
+
+#[test]
+fn shutdown_policy_surfaces_first_error() {
+    for executor in [Executor::Current, Executor::Pool(2)] {
+        let (log, error) = run(executor, ErrorPolicy::Shutdown, &ITEMS, &[]);
+        assert_eq!(log, vec![(1, 0)]);
+        assert_eq!(error.as_deref(), Some("root/child: odd item 1"));
+    }
+}
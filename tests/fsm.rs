@@ -0,0 +1,109 @@
+use doing_more_actors::{FsmActor, FsmState, Message, System, Transition};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Msg {
+    Open,
+    Close,
+    Lock,
+}
+
+impl Message for Msg {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Door {
+    Closed,
+    Opened,
+    Locked,
+}
+
+impl FsmState for Door {}
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Door that closes by itself after 100 millis, logging its actions, the
+/// transitions it makes and the time at which it makes them.
+fn door(log: &Log) -> FsmActor<String, Door, Log, Msg> {
+    let trace = log.clone();
+    FsmActor::new(Door::Closed, log.clone())
+        .on(Door::Closed, |_, _, _, msg| match msg {
+            Msg::Open => Transition::Goto(Door::Opened),
+            Msg::Lock => Transition::Goto(Door::Locked),
+            Msg::Close => Transition::Stay,
+        })
+        .on(Door::Opened, |_, _, _, msg| match msg {
+            Msg::Close => Transition::Goto(Door::Closed),
+            _ => Transition::Stay,
+        })
+        .on(Door::Locked, |_, _, _, _| Transition::Stop)
+        .timeout(Door::Opened, 100, Msg::Close)
+        .on_enter(Door::Opened, |log, _, ctx| {
+            log.lock()
+                .unwrap()
+                .push(format!("enter opened at {}", ctx.now()));
+        })
+        .on_exit(Door::Opened, |log, _, ctx| {
+            log.lock()
+                .unwrap()
+                .push(format!("exit opened at {}", ctx.now()));
+        })
+        .on_exit(Door::Locked, |log, _, _| {
+            log.lock().unwrap().push("exit locked".to_string());
+        })
+        .trace(move |_, from, to| {
+            trace.lock().unwrap().push(format!("{from:?} -> {to:?}"));
+        })
+}
+
+fn entries(entries: &[&str]) -> Vec<String> {
+    entries.iter().map(|entry| entry.to_string()).collect()
+}
+
+#[test]
+fn transitions_run_actions_and_are_traced() {
+    let log = Log::default();
+    let mut sys = System::default();
+    let door = sys.bind("door".to_string(), door(&log));
+    for msg in [Msg::Close, Msg::Open, Msg::Close, Msg::Lock, Msg::Open] {
+        door.send(msg);
+    }
+    let outcome = sys.run_virtual(0);
+    assert!(outcome.is_empty());
+    assert_eq!(
+        *log.lock().unwrap(),
+        entries(&[
+            "Closed -> Opened",
+            "enter opened at 0",
+            "exit opened at 0",
+            "Opened -> Closed",
+            "Closed -> Locked",
+            "exit locked",
+        ])
+    );
+}
+
+#[test]
+fn state_timeouts_fire_unless_the_state_is_left() {
+    let log = Log::default();
+    let mut sys = System::default();
+    let door = sys.bind("door".to_string(), door(&log));
+    door.send(Msg::Open);
+    sys.step(0);
+    assert_eq!(sys.next_deadline(), Some(100));
+    door.send(Msg::Close);
+    sys.step(10);
+    assert_eq!(sys.next_deadline(), None);
+
+    door.send(Msg::Open);
+    let outcome = sys.run_virtual(20);
+    assert!(outcome.is_empty());
+    assert_eq!(
+        log.lock().unwrap()[4..],
+        entries(&[
+            "Closed -> Opened",
+            "enter opened at 20",
+            "exit opened at 120",
+            "Opened -> Closed",
+        ])
+    );
+}
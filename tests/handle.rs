@@ -0,0 +1,117 @@
+use doing_more_actors::{
+    Actor, Closed, Context, Mailbox, Message, Overflow, Shutdown, System, SystemHandle,
+};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+#[derive(Debug, Clone)]
+struct Add(u32);
+
+impl Message for Add {}
+
+#[derive(Debug)]
+struct Sum(u32);
+
+impl Actor for Sum {
+    type T = String;
+    type M = Add;
+
+    fn act(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Add>, Add(n): Add) {
+        self.0 += n;
+    }
+
+    fn on_stop(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Add>) {
+        assert_eq!(self.0, 4 * (1..=10).sum::<u32>());
+    }
+}
+
+/// Records the numbers it receives.
+#[derive(Debug)]
+struct Log(Arc<Mutex<Vec<u32>>>);
+
+impl Actor for Log {
+    type T = String;
+    type M = Add;
+
+    fn act(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Add>, Add(n): Add) {
+        self.0.lock().unwrap().push(n);
+    }
+}
+
+fn assert_shareable<H: Send + Sync + Clone + 'static>(_: &H) {}
+
+#[test]
+fn handle_sends_from_other_threads() {
+    let mut sys = System::default();
+    let tag = "sum".to_string();
+    sys.bind(tag.clone(), Sum(0));
+    let handle: SystemHandle<String, Sum, Add> = sys.handle();
+    assert_shareable(&handle);
+
+    let senders: Vec<_> = (0..4)
+        .map(|_| {
+            let (handle, tag) = (handle.clone(), tag.clone());
+            thread::spawn(move || {
+                for n in 1..=10 {
+                    handle.send(&tag, Add(n)).unwrap();
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.join().unwrap();
+    }
+    handle.shutdown(Shutdown::Drain).unwrap();
+    assert!(sys.run().is_empty());
+}
+
+#[test]
+fn handle_reports_dropped_system() {
+    let sys: System<String, Sum, Add> = System::default();
+    let handle = sys.handle();
+    drop(sys);
+    assert_eq!(handle.send(&"sum".to_string(), Add(1)), Err(Closed));
+    assert_eq!(Closed.to_string(), "actor system is closed");
+}
+
+#[test]
+fn handle_waits_for_room_in_blocking_mailbox() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let tag = "log".to_string();
+    let mailbox = Mailbox::bounded(1, Overflow::Block);
+    sys.bind_with(tag.clone(), Log(received.clone()), mailbox);
+    sys.step(0);
+    let handle = sys.handle();
+    let producer = thread::spawn({
+        let tag = tag.clone();
+        move || {
+            for n in 0..3 {
+                handle.send(&tag, Add(n)).unwrap();
+            }
+        }
+    });
+    let mut max = 0;
+    while received.lock().unwrap().len() < 3 {
+        sys.step(0);
+        max = max.max(sys.queued(&tag));
+    }
+    producer.join().unwrap();
+    assert!(max <= 1, "mailbox held {max} messages");
+    assert_eq!(sys.dead_letters(), 0);
+    assert_eq!(*received.lock().unwrap(), vec![0, 1, 2]);
+}
+
+#[test]
+fn handle_reports_finished_system() {
+    let mut sys = System::default();
+    let tag = "log".to_string();
+    sys.bind(tag.clone(), Log(Arc::default()));
+    let handle = sys.handle();
+    handle.shutdown(Shutdown::Now).unwrap();
+    sys.run();
+    assert_eq!(handle.send(&tag, Add(1)), Err(Closed));
+    assert_eq!(handle.shutdown(Shutdown::Now), Err(Closed));
+}
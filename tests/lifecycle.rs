@@ -0,0 +1,121 @@
+use doing_more_actors::{Actor, Context, DeadLetter, Message, System};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+enum Msg {
+    Item(u32),
+    Tick,
+}
+
+impl Message for Msg {}
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Records its hooks and messages under its name. Posts a tick to itself on
+/// start, if given a delay.
+#[derive(Debug)]
+struct Probe {
+    name: &'static str,
+    tick: Option<u64>,
+    log: Log,
+}
+
+impl Probe {
+    fn new(name: &'static str, log: &Log) -> Self {
+        Self {
+            name,
+            tick: None,
+            log: log.clone(),
+        }
+    }
+
+    fn record(&self, event: String) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{} {event}", self.name));
+    }
+}
+
+impl Actor for Probe {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, _tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Item(n) => self.record(format!("item {n}")),
+            Msg::Tick => self.record(format!("tick at {}", ctx.now())),
+        }
+    }
+
+    fn on_start(&mut self, tag: &String, ctx: &mut Context<String, Self, Msg>) {
+        self.record("start".into());
+        if let Some(delay) = self.tick {
+            ctx.post(tag.clone(), Msg::Tick, delay);
+        }
+    }
+
+    fn on_stop(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Msg>) {
+        self.record("stop".into());
+    }
+
+    fn on_restart(&mut self, _tag: &String, _ctx: &mut Context<String, Self, Msg>) {
+        self.record("restart".into());
+    }
+}
+
+fn events(log: &Log) -> Vec<String> {
+    log.lock().unwrap().clone()
+}
+
+#[test]
+fn rebind_stops_the_old_actor_before_restarting_the_new_one() {
+    let log = Log::default();
+    let tag = "probe".to_string();
+    let mut sys = System::default();
+    sys.bind(tag.clone(), Probe::new("old", &log));
+    sys.step(0);
+    sys.bind(tag.clone(), Probe::new("new", &log))
+        .send(Msg::Item(1));
+    sys.run_virtual(0);
+    assert_eq!(
+        events(&log),
+        ["old start", "old stop", "new restart", "new item 1"]
+    );
+}
+
+#[test]
+fn stop_runs_on_stop_before_queued_messages_become_dead_letters() {
+    let log = Log::default();
+    let tag = "probe".to_string();
+    let mut sys = System::default();
+    let letters = log.clone();
+    sys.on_dead_letter(move |letter: DeadLetter<String, Msg>| {
+        letters
+            .lock()
+            .unwrap()
+            .push(format!("dead {:?}", letter.msg));
+    });
+    let probe = sys.bind(tag.clone(), Probe::new("probe", &log));
+    probe.send(Msg::Item(1));
+    probe.send(Msg::Item(2));
+    sys.context().stop(&tag);
+    sys.run_virtual(0);
+    assert_eq!(
+        events(&log),
+        ["probe start", "probe stop", "dead Item(1)", "dead Item(2)"]
+    );
+}
+
+#[test]
+fn posts_from_on_start_are_delivered() {
+    let log = Log::default();
+    let mut sys = System::default();
+    let probe = Probe {
+        tick: Some(10),
+        ..Probe::new("probe", &log)
+    };
+    sys.bind("probe".to_string(), probe);
+    sys.run_virtual(0);
+    assert_eq!(events(&log), ["probe start", "probe tick at 10"]);
+}
@@ -0,0 +1,214 @@
+use doing_more_actors::{Actor, Context, DeadLetter, Mailbox, Message, Overflow, Reason, System};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Msg {
+    Flood(String, u32),
+    Item(u32),
+    Rejected(u32),
+}
+
+impl Message for Msg {}
+
+/// Records every item it receives. Sends a flood of items to another tag
+/// when asked to, which all arrive before the receiver gets to run.
+#[derive(Debug)]
+struct Node {
+    seen: Arc<Mutex<Vec<Msg>>>,
+}
+
+impl Actor for Node {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, _tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Flood(to, count) => {
+                for n in 0..count {
+                    ctx.send(&to, Msg::Item(n));
+                }
+            }
+            msg => self.seen.lock().unwrap().push(msg),
+        }
+    }
+}
+
+fn flood(overflow: Overflow<String, Msg>) -> (Vec<Msg>, Vec<Msg>, usize, usize) {
+    let sender = Arc::new(Mutex::new(Vec::new()));
+    let receiver = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    sys.bind(
+        "sender".to_string(),
+        Node {
+            seen: sender.clone(),
+        },
+    )
+    .send(Msg::Flood("receiver".to_string(), 5));
+    sys.bind_with(
+        "receiver".to_string(),
+        Node {
+            seen: receiver.clone(),
+        },
+        Mailbox::bounded(2, overflow),
+    );
+    for _ in 0..3 {
+        sys.step(0);
+    }
+    let dropped = sys.dropped(&"receiver".to_string());
+    sys.run_virtual(0);
+    let sender = sender.lock().unwrap().clone();
+    let receiver = receiver.lock().unwrap().clone();
+    (sender, receiver, dropped, sys.dead_letters())
+}
+
+#[test]
+fn drop_newest_keeps_first_messages() {
+    let (_, received, dropped, dead) = flood(Overflow::DropNewest);
+    assert_eq!(received, vec![Msg::Item(0), Msg::Item(1)]);
+    assert_eq!((dropped, dead), (3, 3));
+}
+
+#[test]
+fn drop_oldest_keeps_last_messages() {
+    let (_, received, dropped, dead) = flood(Overflow::DropOldest);
+    assert_eq!(received, vec![Msg::Item(3), Msg::Item(4)]);
+    assert_eq!((dropped, dead), (3, 3));
+}
+
+#[test]
+fn drop_oldest_keeps_urgent_messages() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let receiver = sys.bind_with(
+        "receiver".to_string(),
+        Node {
+            seen: received.clone(),
+        },
+        Mailbox::bounded(2, Overflow::DropOldest),
+    );
+    receiver.send_urgent(Msg::Rejected(0));
+    receiver.send_urgent(Msg::Rejected(1));
+    receiver.send(Msg::Item(0));
+    receiver.send(Msg::Item(1));
+    sys.run_virtual(0);
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![Msg::Rejected(0), Msg::Rejected(1)]
+    );
+    assert_eq!(
+        (sys.dropped(&"receiver".to_string()), sys.dead_letters()),
+        (2, 2)
+    );
+}
+
+#[test]
+fn reject_returns_messages_to_sender() {
+    let overflow = Overflow::reject(|letter: DeadLetter<String, Msg>| {
+        assert_eq!(letter.reason, Reason::Full);
+        match letter.msg {
+            Msg::Item(n) => Msg::Rejected(n),
+            msg => msg,
+        }
+    });
+    let (returned, received, dropped, dead) = flood(overflow);
+    assert_eq!(received, vec![Msg::Item(0), Msg::Item(1)]);
+    assert_eq!(
+        returned,
+        vec![Msg::Rejected(2), Msg::Rejected(3), Msg::Rejected(4)]
+    );
+    assert_eq!((dropped, dead), (3, 0));
+}
+
+#[test]
+fn block_waits_for_room() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let receiver = sys.bind_with(
+        "receiver".to_string(),
+        Node {
+            seen: received.clone(),
+        },
+        Mailbox::bounded(2, Overflow::Block),
+    );
+    let producer = thread::spawn(move || {
+        for n in 0..100 {
+            receiver.send(Msg::Item(n));
+        }
+    });
+    let mut max = 0;
+    while received.lock().unwrap().len() < 100 {
+        sys.step(0);
+        max = max.max(sys.queued(&"receiver".to_string()));
+    }
+    producer.join().unwrap();
+    assert!(max <= 2, "mailbox held {max} messages");
+    assert_eq!(sys.dropped(&"receiver".to_string()), 0);
+    let expected: Vec<Msg> = (0..100).map(Msg::Item).collect();
+    assert_eq!(*received.lock().unwrap(), expected);
+}
+
+#[test]
+fn block_applies_to_references_from_rebinds() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let tag = "receiver".to_string();
+    let mut sys = System::default();
+    let node = || Node {
+        seen: received.clone(),
+    };
+    sys.bind_with(tag.clone(), node(), Mailbox::bounded(1, Overflow::Block));
+    sys.step(0);
+    let receiver = sys.bind(tag.clone(), node());
+    sys.step(0);
+    let producer = thread::spawn(move || {
+        for n in 0..20 {
+            receiver.send(Msg::Item(n));
+        }
+    });
+    loop {
+        let finished = producer.is_finished();
+        sys.step(0);
+        if finished && sys.is_idle() {
+            break;
+        }
+    }
+    assert_eq!(sys.dead_letters(), 0);
+    let expected: Vec<Msg> = (0..20).map(Msg::Item).collect();
+    assert_eq!(*received.lock().unwrap(), expected);
+}
+
+#[test]
+fn urgent_and_prioritised_messages_go_first() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let mailbox = Mailbox::unbounded().priority(|msg: &Msg| match msg {
+        Msg::Item(n) => (*n % 3) as u8,
+        _ => 0,
+    });
+    let receiver = sys.bind_with(
+        "receiver".to_string(),
+        Node {
+            seen: received.clone(),
+        },
+        mailbox,
+    );
+    for n in 0..6 {
+        receiver.send(Msg::Item(n));
+    }
+    receiver.send_urgent(Msg::Rejected(0));
+    sys.run_virtual(0);
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![
+            Msg::Rejected(0),
+            Msg::Item(2),
+            Msg::Item(5),
+            Msg::Item(1),
+            Msg::Item(4),
+            Msg::Item(0),
+            Msg::Item(3),
+        ]
+    );
+}
@@ -0,0 +1,140 @@
+use doing_more_actors::{Actor, Context, Executor, Message, Scheduling, System, Tick};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+struct Item(u32);
+
+impl Message for Item {}
+
+/// Records the tag and item of every message it processes.
+#[derive(Debug)]
+struct Logger {
+    log: Arc<Mutex<Vec<(String, u32)>>>,
+}
+
+impl Actor for Logger {
+    type T = String;
+    type M = Item;
+
+    fn act(&mut self, tag: &String, _ctx: &mut Context<String, Self, Item>, Item(n): Item) {
+        self.log.lock().unwrap().push((tag.clone(), n));
+    }
+}
+
+fn run(executor: Executor, scheduling: Scheduling) -> Vec<(String, u32)> {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::new(executor);
+    sys.set_scheduling(scheduling);
+    let tags = ["c", "a", "b"];
+    let refs: Vec<_> = tags
+        .iter()
+        .map(|tag| sys.bind(tag.to_string(), Logger { log: log.clone() }))
+        .collect();
+    for n in 0..3 {
+        for actor in &refs {
+            actor.send(Item(n));
+        }
+    }
+    sys.run_virtual(0);
+    let log = log.lock().unwrap();
+    log.clone()
+}
+
+fn entries(entries: &[(&str, u32)]) -> Vec<(String, u32)> {
+    entries
+        .iter()
+        .map(|(tag, n)| (tag.to_string(), *n))
+        .collect()
+}
+
+#[test]
+fn actors_take_turns_in_order_they_became_ready() {
+    let log = run(Executor::Current, Scheduling::default());
+    let expected = entries(&[
+        ("c", 0),
+        ("a", 0),
+        ("b", 0),
+        ("c", 1),
+        ("a", 1),
+        ("b", 1),
+        ("c", 2),
+        ("a", 2),
+        ("b", 2),
+    ]);
+    assert_eq!(log, expected);
+}
+
+#[test]
+fn throughput_limits_messages_per_turn() {
+    let log = run(Executor::Current, Scheduling::RoundRobin(2));
+    let expected = entries(&[
+        ("c", 0),
+        ("c", 1),
+        ("a", 0),
+        ("a", 1),
+        ("b", 0),
+        ("b", 1),
+        ("c", 2),
+        ("a", 2),
+        ("b", 2),
+    ]);
+    assert_eq!(log, expected);
+
+    let log = run(Executor::Current, Scheduling::Exhaustive);
+    let expected = entries(&[
+        ("c", 0),
+        ("c", 1),
+        ("c", 2),
+        ("a", 0),
+        ("a", 1),
+        ("a", 2),
+        ("b", 0),
+        ("b", 1),
+        ("b", 2),
+    ]);
+    assert_eq!(log, expected);
+}
+
+#[test]
+fn pool_processes_batches_in_order() {
+    let log = run(Executor::Pool(2), Scheduling::RoundRobin(2));
+    for tag in ["a", "b", "c"] {
+        let items: Vec<u32> = log
+            .iter()
+            .filter(|(t, _)| t == tag)
+            .map(|(_, n)| *n)
+            .collect();
+        assert_eq!(items, vec![0, 1, 2]);
+    }
+}
+
+#[test]
+fn tick_processes_up_to_budget() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let actor = sys.bind("a".to_string(), Logger { log: log.clone() });
+    for n in 0..5 {
+        actor.send(Item(n));
+    }
+    actor.post(Item(5), 100);
+
+    let tick = sys.tick(0, 2);
+    assert_eq!(tick.processed, 2);
+    assert!(!tick.idle);
+
+    let tick = sys.tick(10, 10);
+    assert_eq!(
+        tick,
+        Tick {
+            processed: 3,
+            next_deadline: Some(100),
+            idle: true,
+            done: false,
+        }
+    );
+
+    let tick = sys.tick(100, 10);
+    assert_eq!((tick.processed, tick.next_deadline), (1, None));
+    assert_eq!(log.lock().unwrap().len(), 6);
+    assert!(sys.finish().is_empty());
+}
@@ -0,0 +1,74 @@
+use doing_more_actors::{Actor, Context, Message, Shutdown, System};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Item(u32);
+
+impl Message for Item {}
+
+/// Records the items it processes. Item 0 shuts the system down in the given
+/// mode and posts item 9 for much later; every item below 3 is followed by
+/// the next one.
+#[derive(Debug)]
+struct Chain {
+    mode: Shutdown,
+    seen: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Actor for Chain {
+    type T = String;
+    type M = Item;
+
+    fn act(&mut self, tag: &String, ctx: &mut Context<String, Self, Item>, Item(n): Item) {
+        self.seen.lock().unwrap().push(n);
+        if n == 0 {
+            ctx.shutdown(self.mode);
+            ctx.post(tag.clone(), Item(9), 1000);
+        }
+        if n < 3 {
+            ctx.send(tag, Item(n + 1));
+        }
+    }
+}
+
+fn run(mode: Shutdown, items: &[u32]) -> (Vec<u32>, Vec<Item>, Vec<Item>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let chain = sys.bind(
+        "chain".to_string(),
+        Chain {
+            mode,
+            seen: seen.clone(),
+        },
+    );
+    for n in items {
+        chain.send(Item(*n));
+    }
+    let outcome = sys.run_virtual(0);
+    let queued = outcome.queued.into_iter().map(|(_, msg)| msg).collect();
+    let posted = outcome.posted.into_iter().map(|(_, msg)| msg).collect();
+    let seen = seen.lock().unwrap();
+    (seen.clone(), queued, posted)
+}
+
+#[test]
+fn shutdown_now_leaves_messages_queued() {
+    let (seen, queued, _) = run(Shutdown::Now, &[0, 5, 6]);
+    assert_eq!(seen, vec![0]);
+    assert_eq!(queued, vec![Item(5), Item(6), Item(1)]);
+}
+
+#[test]
+fn drain_processes_messages_sent_while_draining() {
+    let (seen, queued, _) = run(Shutdown::Drain, &[0]);
+    assert_eq!(seen, vec![0, 1, 2, 3]);
+    assert!(queued.is_empty());
+}
+
+#[test]
+fn timers_not_yet_due_are_listed_as_posted() {
+    for mode in [Shutdown::Now, Shutdown::Drain] {
+        let (_, _, posted) = run(mode, &[0]);
+        assert_eq!(posted, vec![Item(9)]);
+    }
+}
@@ -0,0 +1,103 @@
+use doing_more_actors::{
+    Actor, Context, DeadLetter, Executor, Mailbox, Message, Reason, Scheduling, System,
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Msg {
+    Item(u32),
+    Ready,
+}
+
+impl Message for Msg {}
+
+/// Stashes items until it is ready, then records them in order.
+#[derive(Debug)]
+struct Loader {
+    ready: bool,
+    seen: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Actor for Loader {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, _tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Item(n) if self.ready => self.seen.lock().unwrap().push(n),
+            Msg::Item(_) => ctx.stash(msg),
+            Msg::Ready => {
+                self.ready = true;
+                ctx.unstash_all();
+            }
+        }
+    }
+}
+
+fn loader() -> (Loader, Arc<Mutex<Vec<u32>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let loader = Loader {
+        ready: false,
+        seen: seen.clone(),
+    };
+    (loader, seen)
+}
+
+#[test]
+fn stashed_messages_come_back_ahead_of_the_mailbox() {
+    let executors = [Executor::Current, Executor::Pool(2)];
+    let schedulings = [
+        Scheduling::RoundRobin(1),
+        Scheduling::RoundRobin(10),
+        Scheduling::Exhaustive,
+    ];
+    for (executor, scheduling) in executors
+        .into_iter()
+        .flat_map(|executor| schedulings.map(|scheduling| (executor, scheduling)))
+    {
+        let mut sys = System::new(executor);
+        sys.set_scheduling(scheduling);
+        let (actor, seen) = loader();
+        let actor = sys.bind("loader".to_string(), actor);
+        let msgs = [
+            Msg::Item(0),
+            Msg::Item(1),
+            Msg::Ready,
+            Msg::Item(2),
+            Msg::Item(3),
+        ];
+        for msg in msgs {
+            actor.send(msg);
+        }
+        assert!(sys.run_virtual(0).is_empty());
+        assert_eq!(*seen.lock().unwrap(), vec![0, 1, 2, 3]);
+    }
+}
+
+#[test]
+fn stash_is_bounded() {
+    let letters = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let log = letters.clone();
+    sys.on_dead_letter(move |letter: DeadLetter<String, Msg>| {
+        log.lock().unwrap().push((letter.msg, letter.reason));
+    });
+    let tag = "loader".to_string();
+    let (actor, _) = loader();
+    let actor = sys.bind_with(tag.clone(), actor, Mailbox::unbounded().stash(2));
+    for n in 0..3 {
+        actor.send(Msg::Item(n));
+    }
+    for _ in 0..3 {
+        sys.step(0);
+    }
+    assert_eq!(sys.stashed(&tag), 2);
+    assert_eq!(
+        *letters.lock().unwrap(),
+        vec![(Msg::Item(2), Reason::Stash)]
+    );
+
+    let outcome = sys.finish();
+    let queued: Vec<Msg> = outcome.queued.into_iter().map(|(_, msg)| msg).collect();
+    assert_eq!(queued, vec![Msg::Item(0), Msg::Item(1)]);
+}
@@ -0,0 +1,93 @@
+use doing_more_actors::{Actor, Context, Message, Millis, Strategy, Supervision, System};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+struct Panic;
+
+impl Message for Panic {}
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Records every start of its tag. A node with a supervision binds three
+/// supervised children, `c0` to `c2`, in that order.
+#[derive(Debug)]
+struct Node {
+    log: Log,
+    children: Option<Supervision>,
+}
+
+impl Actor for Node {
+    type T = String;
+    type M = Panic;
+
+    fn act(&mut self, tag: &String, _ctx: &mut Context<String, Self, Panic>, _msg: Panic) {
+        panic!("{tag} failed");
+    }
+
+    fn on_start(&mut self, tag: &String, ctx: &mut Context<String, Self, Panic>) {
+        self.log.lock().unwrap().push(tag.clone());
+        if let Some(supervision) = self.children {
+            ctx.set_supervision(tag, supervision);
+            for child in ["c0", "c1", "c2"] {
+                let log = self.log.clone();
+                ctx.supervise(format!("{tag}/{child}"), move || Node {
+                    log: log.clone(),
+                    children: None,
+                });
+            }
+        }
+    }
+}
+
+/// Makes the given children of a supervised root fail at the given times, and
+/// returns how many times the root and each child started.
+fn run(supervision: Supervision, failures: &[(&str, Millis)]) -> [usize; 4] {
+    let log = Log::default();
+    let mut sys = System::default();
+    let mut ctx = sys.context();
+    let root = log.clone();
+    ctx.supervise("root".to_string(), move || Node {
+        log: root.clone(),
+        children: Some(supervision),
+    });
+    for (child, at) in failures {
+        ctx.post(format!("root/{child}"), Panic, *at);
+    }
+    sys.run_virtual(0);
+    let log = log.lock().unwrap();
+    ["root", "root/c0", "root/c1", "root/c2"]
+        .map(|tag| log.iter().filter(|started| *started == tag).count())
+}
+
+#[test]
+fn one_for_one_restarts_the_failed_child() {
+    let starts = run(Supervision::new(Strategy::OneForOne), &[("c1", 1)]);
+    assert_eq!(starts, [1, 1, 2, 1]);
+}
+
+#[test]
+fn one_for_all_restarts_every_child() {
+    let starts = run(Supervision::new(Strategy::OneForAll), &[("c1", 1)]);
+    assert_eq!(starts, [1, 2, 2, 2]);
+}
+
+#[test]
+fn rest_for_one_restarts_the_failed_child_and_those_after_it() {
+    let starts = run(Supervision::new(Strategy::RestForOne), &[("c1", 1)]);
+    assert_eq!(starts, [1, 1, 2, 2]);
+}
+
+#[test]
+fn failures_beyond_the_limit_escalate_to_the_parent() {
+    let supervision = Supervision::new(Strategy::OneForOne).limit(1, 100);
+    // The restarted root binds a fresh set of children.
+    let starts = run(supervision, &[("c1", 1), ("c1", 2)]);
+    assert_eq!(starts, [2, 2, 3, 2]);
+}
+
+#[test]
+fn failures_outside_the_window_do_not_count() {
+    let supervision = Supervision::new(Strategy::OneForOne).limit(1, 100);
+    let starts = run(supervision, &[("c1", 1), ("c1", 200)]);
+    assert_eq!(starts, [1, 1, 3, 1]);
+}
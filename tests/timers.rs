@@ -0,0 +1,212 @@
+use doing_more_actors::{Actor, Context, Message, Millis, Schedule, System, TimerId};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone)]
+enum Msg {
+    Schedule(Vec<(u32, Millis)>),
+    Every(u32, Schedule),
+    Cancel(u32),
+    Reschedule(u32, Millis),
+    Fired(u32),
+}
+
+impl Message for Msg {}
+
+/// Posts the scheduled messages to itself and records when each one fires,
+/// stopping after `expected` deliveries.
+#[derive(Debug)]
+struct Recorder {
+    fired: Arc<Mutex<Vec<(u32, Millis)>>>,
+    expected: usize,
+    timers: HashMap<u32, TimerId>,
+}
+
+impl Recorder {
+    fn new(fired: Arc<Mutex<Vec<(u32, Millis)>>>, expected: usize) -> Self {
+        Self {
+            fired,
+            expected,
+            timers: HashMap::new(),
+        }
+    }
+}
+
+impl Actor for Recorder {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Schedule(posts) => {
+                for (id, millis) in posts {
+                    let timer = ctx.post(tag.clone(), Msg::Fired(id), millis);
+                    self.timers.insert(id, timer);
+                }
+            }
+            Msg::Every(id, schedule) => {
+                let timer = ctx.schedule(tag.clone(), Msg::Fired(id), schedule);
+                self.timers.insert(id, timer);
+            }
+            Msg::Cancel(id) => ctx.cancel(self.timers[&id]),
+            Msg::Reschedule(id, millis) => ctx.reschedule(self.timers[&id], millis),
+            Msg::Fired(id) => {
+                let mut fired = self.fired.lock().unwrap();
+                fired.push((id, ctx.now()));
+                if fired.len() == self.expected {
+                    ctx.stop(tag);
+                }
+            }
+        }
+    }
+}
+
+fn run_virtual(msgs: Vec<Msg>, expected: usize) -> Vec<(u32, Millis)> {
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let recorder = sys.bind(
+        "recorder".to_string(),
+        Recorder::new(fired.clone(), expected),
+    );
+    for msg in msgs {
+        recorder.send(msg);
+    }
+    sys.run_virtual(0);
+    let fired = fired.lock().unwrap();
+    fired.clone()
+}
+
+#[test]
+fn short_post_is_not_delayed_by_long_post() {
+    let fired = run_virtual(vec![Msg::Schedule(vec![(1, 10_000), (2, 10)])], 2);
+    assert_eq!(fired, vec![(2, 10), (1, 10_000)]);
+}
+
+#[test]
+fn interleaved_posts_fire_in_deadline_order() {
+    let posts = vec![(1, 50), (2, 10), (3, 3_000), (4, 30), (5, 1), (6, 500)];
+    let fired = run_virtual(vec![Msg::Schedule(posts)], 6);
+    assert_eq!(
+        fired,
+        vec![(5, 1), (2, 10), (4, 30), (1, 50), (6, 500), (3, 3_000)]
+    );
+}
+
+#[test]
+fn equal_deadlines_fire_in_posting_order() {
+    let posts = vec![(1, 20), (2, 10), (3, 20), (4, 10), (5, 20)];
+    let fired = run_virtual(vec![Msg::Schedule(posts)], 5);
+    assert_eq!(fired, vec![(2, 10), (4, 10), (1, 20), (3, 20), (5, 20)]);
+}
+
+#[test]
+fn cancelled_and_rescheduled_posts() {
+    let msgs = vec![
+        Msg::Schedule(vec![(1, 10), (2, 20), (3, 30), (4, 40)]),
+        Msg::Cancel(2),
+        Msg::Reschedule(3, 5),
+        Msg::Reschedule(1, 50),
+    ];
+    let fired = run_virtual(msgs, 3);
+    assert_eq!(fired, vec![(3, 5), (4, 40), (1, 50)]);
+}
+
+#[test]
+fn timeout_reset_on_every_message_fires_once() {
+    let mut msgs = vec![Msg::Schedule(vec![(1, 10), (2, 5_000)])];
+    msgs.extend((1..=100).map(|n| Msg::Reschedule(1, 10 + n)));
+    let fired = run_virtual(msgs, 2);
+    assert_eq!(fired, vec![(1, 110), (2, 5_000)]);
+}
+
+#[test]
+fn recurring_post_fires_at_fixed_rate() {
+    let msgs = vec![
+        Msg::Every(1, Schedule::every(30).times(3)),
+        Msg::Every(2, Schedule::every(25).delay(0)),
+        Msg::Schedule(vec![(3, 60)]),
+    ];
+    let fired = run_virtual(msgs, 8);
+    assert_eq!(
+        fired,
+        vec![
+            (2, 0),
+            (2, 25),
+            (1, 30),
+            (2, 50),
+            (3, 60),
+            (1, 60),
+            (2, 75),
+            (1, 90),
+        ]
+    );
+}
+
+#[test]
+fn recurring_post_with_jitter_does_not_drift() {
+    let fired = run_virtual(vec![Msg::Every(1, Schedule::every(100).jitter(9))], 50);
+    for (n, (_, at)) in fired.iter().enumerate() {
+        let due = (n as Millis + 1) * 100;
+        assert!((due..due + 10).contains(at), "firing {n} at {at}");
+    }
+}
+
+#[test]
+fn jitter_repeats_across_runs() {
+    let schedule = Schedule::every(100).jitter(50);
+    let runs: Vec<_> = (0..2)
+        .map(|_| run_virtual(vec![Msg::Every(1, schedule)], 5))
+        .collect();
+    assert_eq!(runs[0], runs[1]);
+}
+
+#[test]
+fn zero_period_and_unbounded_jitter_keep_timers_finite() {
+    let msgs = vec![
+        Msg::Every(1, Schedule::every(0).delay(0).times(3)),
+        Msg::Every(2, Schedule::every(10).jitter(Millis::MAX).times(2)),
+    ];
+    let fired = run_virtual(msgs, 5);
+    assert_eq!(fired[..3], [(1, 0), (1, 1), (1, 2)]);
+    assert!(fired[3..].iter().all(|(id, at)| *id == 2 && *at >= 10));
+}
+
+#[test]
+fn cancelled_recurring_post_stops_firing() {
+    let msgs = vec![
+        Msg::Every(1, Schedule::every(10)),
+        Msg::Schedule(vec![(2, 35)]),
+        Msg::Every(3, Schedule::every(50).times(1)),
+    ];
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    let recorder = sys.bind("recorder".to_string(), Recorder::new(fired.clone(), 6));
+    for msg in msgs {
+        recorder.send(msg);
+    }
+    for now in [0, 0, 0].into_iter().chain(1..=35) {
+        sys.step(now);
+    }
+    recorder.send(Msg::Cancel(1));
+    sys.run_virtual(35);
+    let fired = fired.lock().unwrap();
+    assert_eq!(*fired, vec![(1, 10), (1, 20), (1, 30), (2, 35), (3, 50)]);
+}
+
+#[test]
+fn short_post_fires_on_time_on_wall_clock() {
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let mut sys = System::default();
+    sys.bind("recorder".to_string(), Recorder::new(fired.clone(), 1))
+        .send(Msg::Schedule(vec![(1, 10_000), (2, 10)]));
+
+    let started = Instant::now();
+    let outcome = sys.run();
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(fired.lock().unwrap().len(), 1);
+    assert_eq!(fired.lock().unwrap()[0].0, 2);
+    assert_eq!(outcome.posted.len(), 1);
+}
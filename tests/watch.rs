@@ -0,0 +1,107 @@
+use doing_more_actors::{Actor, Context, Exit, Message, System, Terminated};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+enum Msg {
+    Watch(String),
+    Unwatch(String),
+    Terminated(Terminated<String>),
+    Stop,
+    Panic,
+}
+
+impl Message for Msg {}
+
+type Log = Arc<Mutex<Vec<(String, Exit)>>>;
+
+/// Watches the tags it is told to, recording every termination it hears of.
+#[derive(Debug)]
+struct Watcher {
+    log: Log,
+}
+
+impl Actor for Watcher {
+    type T = String;
+    type M = Msg;
+
+    fn act(&mut self, tag: &String, ctx: &mut Context<String, Self, Msg>, msg: Msg) {
+        match msg {
+            Msg::Watch(other) => ctx.watch(&other, Msg::Terminated),
+            Msg::Unwatch(other) => ctx.unwatch(&other),
+            Msg::Terminated(Terminated { tag, exit }) => {
+                self.log.lock().unwrap().push((tag, exit));
+            }
+            Msg::Stop => ctx.stop(tag),
+            Msg::Panic => panic!("watched actor panicked"),
+        }
+    }
+}
+
+fn watcher() -> (Watcher, Log) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    (Watcher { log: log.clone() }, log)
+}
+
+/// Runs the system until it has no more work at the current time.
+fn settle(sys: &mut System<String, Watcher, Msg>) {
+    while !sys.tick(0, 100).idle {}
+}
+
+#[test]
+fn watchers_are_told_when_actors_stop() {
+    let mut sys = System::default();
+    let (boss, log) = watcher();
+    let boss = sys.bind("boss".to_string(), boss);
+    let a = sys.bind("a".to_string(), watcher().0);
+    let b = sys.bind("b".to_string(), watcher().0);
+    for msg in [
+        Msg::Watch("a".to_string()),
+        Msg::Watch("missing".to_string()),
+        Msg::Watch("b".to_string()),
+        Msg::Unwatch("b".to_string()),
+    ] {
+        boss.send(msg);
+    }
+    settle(&mut sys);
+
+    a.send(Msg::Stop);
+    b.send(Msg::Stop);
+    assert!(sys.run_virtual(0).is_empty());
+    let log = log.lock().unwrap();
+    assert_eq!(
+        *log,
+        vec![
+            ("missing".to_string(), Exit::Unbound),
+            ("a".to_string(), Exit::Stopped),
+        ]
+    );
+}
+
+#[test]
+fn watchers_are_told_of_failures_and_replacements() {
+    let mut sys = System::default();
+    let (boss, log) = watcher();
+    let boss = sys.bind("boss".to_string(), boss);
+    let a = sys.context().supervise("a".to_string(), || watcher().0);
+    sys.bind("b".to_string(), watcher().0);
+    boss.send(Msg::Watch("a".to_string()));
+    boss.send(Msg::Watch("b".to_string()));
+    settle(&mut sys);
+
+    a.send(Msg::Panic);
+    settle(&mut sys);
+    sys.bind("b".to_string(), watcher().0);
+    settle(&mut sys);
+
+    // A watch ends with its first notification.
+    a.send(Msg::Stop);
+    assert!(sys.run_virtual(0).is_empty());
+    let log = log.lock().unwrap();
+    assert_eq!(
+        *log,
+        vec![
+            ("a".to_string(), Exit::Failed),
+            ("b".to_string(), Exit::Replaced),
+        ]
+    );
+}